@@ -0,0 +1,59 @@
+// CRT pass applied to the offscreen frame: screen curvature, bloom and scanlines.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+const CURVATURE: f32 = 0.06;
+const BLOOM_STRENGTH: f32 = 0.45;
+const BLOOM_RADIUS: f32 = 2.5;
+const SCANLINE_DEPTH: f32 = 0.3;
+const VIGNETTE: f32 = 0.25;
+const PI: f32 = 3.14159265;
+
+// Bends the picture like the glass of a tube, the corners are pulled outside of [0, 1]
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    let centered = uv * 2.0 - 1.0;
+    let bent = centered * (1.0 + centered.yx * centered.yx * CURVATURE);
+    return bent * 0.5 + 0.5;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = curve(in.uv);
+    let size = vec2<f32>(textureDimensions(t));
+    let texel = BLOOM_RADIUS / size;
+
+    // Every sample is taken before any branch, textureSample needs uniform control flow
+    let base = textureSample(t, s, uv).rgb;
+    var bloom = textureSample(t, s, uv + vec2<f32>(texel.x, 0.0)).rgb;
+    bloom += textureSample(t, s, uv - vec2<f32>(texel.x, 0.0)).rgb;
+    bloom += textureSample(t, s, uv + vec2<f32>(0.0, texel.y)).rgb;
+    bloom += textureSample(t, s, uv - vec2<f32>(0.0, texel.y)).rgb;
+    bloom += textureSample(t, s, uv + texel).rgb;
+    bloom += textureSample(t, s, uv - texel).rgb;
+    bloom += textureSample(t, s, uv + vec2<f32>(texel.x, -texel.y)).rgb;
+    bloom += textureSample(t, s, uv + vec2<f32>(-texel.x, texel.y)).rgb;
+
+    var color = base + bloom / 8.0 * BLOOM_STRENGTH;
+
+    // One dark line every other row of pixels
+    let scanline = 1.0 - SCANLINE_DEPTH * (0.5 + 0.5 * cos(uv.y * size.y * PI));
+    color *= scanline;
+
+    let centered = uv * 2.0 - 1.0;
+    color *= 1.0 - VIGNETTE * dot(centered, centered) * 0.5;
+
+    // Outside of the bent screen is the black bezel
+    let inside = step(0.0, uv.x) * step(uv.x, 1.0) * step(0.0, uv.y) * step(uv.y, 1.0);
+
+    return vec4<f32>(color * inside, 1.0) * in.color;
+}
@@ -0,0 +1,210 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::assets::Assets;
+use crate::input::InputState;
+use crate::powerups::PowerupType;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ActorType {
+    Player,
+    PlayerBullet,
+    EnemyBulletSlow,
+    EnemyBulletFast,
+    EnemyBulletZigZag,
+    EnemyBulletSquiggly,
+    EnemyBulletPlunger,
+    EnemyBulletRolling,
+    EnemyA,
+    EnemyB,
+    EnemyC,
+    Ufo,
+    Shield,
+    Powerup(PowerupType)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor{
+    pub tag: ActorType,
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub size: Vec2,
+    pub scale: Vec2,
+    pub hp: f32,
+    pub alive: bool,
+    //Position at the start of the last simulation step, None until the actor lived a full step
+    pub previous_position: Option<Vec2>,
+    //Set on a player bullet by its first hit, a piercing bullet hitting more enemies is still one hit
+    #[serde(default)]
+    pub has_hit: bool,
+}
+
+//Emitted when damage destroys an actor, actors removed without being destroyed emit nothing
+#[derive(Debug, Copy, Clone)]
+pub struct ActorDeath {
+    pub tag: ActorType,
+    pub position: Vec2,
+}
+
+impl Actor {
+    //Returns true on the first hit of a bullet
+    pub fn mark_hit(&mut self) -> bool {
+        let first_hit = !self.has_hit;
+        self.has_hit = true;
+        first_hit
+    }
+
+    pub fn get_rect(&self) -> Rect {
+        Rect{ x: self.position.x - (self.size.x / 2.0), y: self.position.y - (self.size.y / 2.0), w: self.size.x, h: self.size.y }
+    }
+
+    //Position between the last two simulation steps, alpha is the fraction of the next step already elapsed
+    pub fn draw_position(&self, alpha: f32) -> Vec2 {
+        match self.previous_position {
+            Some(previous_position) => previous_position.lerp(self.position, alpha),
+            None => self.position
+        }
+    }
+
+    pub fn store_previous_position(&mut self) {
+        self.previous_position = Some(self.position);
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    //Removes the actor at the end of the tick
+    pub fn kill(&mut self) {
+        self.alive = false;
+    }
+
+    //Returns the death event when the damage destroys the actor
+    pub fn damage(&mut self, amount: f32) -> Option<ActorDeath> {
+        if !self.alive {
+            return None;
+        }
+
+        self.hp -= amount;
+
+        if self.hp > 0.0 {
+            return None;
+        }
+
+        self.alive = false;
+        Some(ActorDeath { tag: self.tag, position: self.position })
+    }
+}
+
+pub const PLAYER_LIFE : f32 = 3.0;
+pub const BULLET_LIFE : f32 = 1.0;
+pub const PLAYER_SPEED : f32 = 320.0; //320
+pub const PLAYER_SHOT_TIME : f32 = 0.5;
+pub const PLAYER_BULLET_SPEED : f32 = 750.0;    //750
+pub const ENEMY_BULLET_SPEED_SLOW : f32 = 350.0; //350
+pub const ENEMY_BULLET_SPEED_FAST : f32 = 550.0; //550
+pub const ENEMY_BULLET_ZIGZAG_PERIOD : f32 = 40.0;
+pub const ENEMY_BULLET_ZIGZAG_SWAY : f32 = 0.5;
+
+pub fn create_player() -> Actor {
+    Actor {
+        tag: ActorType::Player,
+        position: Vec2::ZERO,
+        direction: Vec2::ZERO,
+        size: Vec2::ZERO,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: PLAYER_LIFE,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+     }
+}
+
+pub fn create_bullet(bullet_type: ActorType) -> Actor {
+    Actor {
+        tag: bullet_type,
+        position: Vec2::ZERO,
+        direction: Vec2::ZERO,
+        size: Vec2::ZERO,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: BULLET_LIFE,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+     }
+}
+
+pub fn player_handle_input(actor: &mut Actor, input: &InputState, dt: f32) {
+    actor.direction.x = 0.0;
+
+    if input.left {
+        actor.direction.x += -1.0;
+    }
+    if input.right {
+        actor.direction.x += 1.0;
+    }
+
+    actor.position.x += actor.direction.x * PLAYER_SPEED * dt;
+}
+
+pub fn player_check_collision_with_walls(actor: &mut Actor, window_size: Vec2) {
+    let left_edge = actor.position.x - (actor.size.x / 2.0);
+    let right_edge = actor.position.x + (actor.size.x / 2.0);
+
+    if left_edge < -window_size.x / 2.0 {
+        actor.position.x = -window_size.x / 2.0 + actor.size.x / 2.0;
+    }
+    else if right_edge > window_size.x / 2.0 {
+        actor.position.x = (window_size.x / 2.0) - (actor.size.x / 2.0);
+    }
+}
+
+pub fn update_bullet_position(actor: &mut Actor, dt: f32) {
+    let speed = match actor.tag {
+        ActorType::PlayerBullet => PLAYER_BULLET_SPEED,
+        ActorType::EnemyBulletSlow => ENEMY_BULLET_SPEED_SLOW,
+        ActorType::EnemyBulletFast => ENEMY_BULLET_SPEED_FAST,
+        ActorType::EnemyBulletZigZag => ENEMY_BULLET_SPEED_SLOW,
+        ActorType::EnemyBulletSquiggly | ActorType::EnemyBulletPlunger | ActorType::EnemyBulletRolling => ENEMY_BULLET_SPEED_SLOW,
+        _ => 1.0
+    };
+
+    //Zig-zag bullets change their horizontal direction every ENEMY_BULLET_ZIGZAG_PERIOD pixels
+    if let ActorType::EnemyBulletZigZag = actor.tag {
+        let segment = (actor.position.y / ENEMY_BULLET_ZIGZAG_PERIOD).floor() as i32;
+        actor.direction.x = if segment.rem_euclid(2) == 0 { ENEMY_BULLET_ZIGZAG_SWAY } else { -ENEMY_BULLET_ZIGZAG_SWAY };
+    }
+
+    actor.position += actor.direction * (speed * dt);
+}
+
+pub fn handle_out_off_screen(actor: &mut Actor, window_size: Vec2) {
+    if  actor.position.y < -window_size.y / 2.0 ||
+        actor.position.y > window_size.y / 2.0 ||
+        actor.position.x < -window_size.x / 2.0 ||
+        actor.position.x > window_size.x / 2.0 {
+        actor.kill();
+    }
+}
+
+pub fn world_to_screen_coords(screen_width: f32, screen_height: f32, point: Vec2) -> Vec2 {
+    let x = point.x + screen_width / 2.0;
+    let y = point.y + screen_height / 2.0;
+    Vec2::new(x, y)
+}
+
+pub fn draw_actor(assets: &mut Assets, canvas: &mut graphics::Canvas, actor: &Actor, world_coords: (f32, f32), alpha: f32) {
+    draw_actor_image(canvas, assets.actor_image(actor), actor, world_coords, alpha);
+}
+
+pub fn draw_actor_image(canvas: &mut graphics::Canvas, image: &graphics::Image, actor: &Actor, world_coords: (f32, f32), alpha: f32) {
+    let (screen_w, screen_h) = world_coords;
+    let pos = world_to_screen_coords(screen_w, screen_h, actor.draw_position(alpha));
+    let drawparams = graphics::DrawParam::new()
+        .dest(pos)
+        .scale(actor.scale)
+        .offset(Vec2::new(0.5, 0.5));
+
+    canvas.draw(image, drawparams);
+}
@@ -0,0 +1,183 @@
+use ggez::audio::{self, SoundSource};
+use ggez::graphics;
+use ggez::{Context, GameResult, GameError};
+
+use crate::actor::{Actor, ActorType};
+use crate::font::BitmapFont;
+use crate::powerups::{POWERUP_SIZE, POWERUP_TYPES};
+
+pub struct Assets {
+    pub player_image: graphics::Image,
+    pub player_bullet_image: graphics::Image,
+    pub enemy_bullet_slow_image: graphics::Image,
+    pub enemy_bullet_fast_image: graphics::Image,
+    pub shield_image: graphics::Image,
+    pub ufo_image: graphics::Image,
+    pub player_shot_sound: audio::Source,
+    pub hit_sound: audio::Source,
+    pub extra_life_sound: audio::Source,
+    pub enemie_images: Vec<graphics::Image>,
+    pub enemie_damaged_images: Vec<graphics::Image>,
+    pub powerup_images: Vec<graphics::Image>,
+    pub font: BitmapFont,
+    //White 1x1 image stretched and tinted to draw plain rectangles
+    pub pixel_image: graphics::Image,
+}
+
+impl Assets {
+    pub fn new(ctx: &mut Context) -> Assets {
+        let player_image = match graphics::Image::from_path(ctx, "/player.png")  {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load player image: {:?}", error),
+        };
+
+        let player_bullet_image = match graphics::Image::from_path(ctx, "/player_bullet.png") {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load player bullet image: {:?}", error),
+        };
+
+        let enemy_bullet_slow_image = match graphics::Image::from_path(ctx, "/enemy_bullet_slow.png") {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load enemy bullet slow image: {:?}", error),
+        };
+
+        let enemy_bullet_fast_image = match graphics::Image::from_path(ctx, "/enemy_bullet_fast.png") {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load enemy bullet fast image: {:?}", error),
+        };
+
+        let mut enemie_images: Vec<graphics::Image> = Vec::with_capacity(3);
+        let mut enemie_damaged_images: Vec<graphics::Image> = Vec::with_capacity(3);
+
+        for i in 1..4 {
+            let enemie = match graphics::Image::from_path(ctx, format!("/invader{}.png", i)) {
+                Ok(image) => image,
+                Err(error) => panic!("Can't load enemie {} image: {:?}", i, error),
+            };
+
+            let damaged_enemie = match load_damaged_image(ctx, &format!("/invader{}.png", i)) {
+                Ok(image) => image,
+                Err(error) => panic!("Can't create damaged enemie {} image: {:?}", i, error),
+            };
+
+            enemie_images.push(enemie);
+            enemie_damaged_images.push(damaged_enemie);
+        }
+
+        let shield_image = match graphics::Image::from_path(ctx, "/shield.png") {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load shield image: {:?}", error),
+        };
+
+        let ufo_image = match graphics::Image::from_path(ctx, "/ufo-small.png") {
+            Ok(image) => image,
+            Err(error) => panic!("Can't load ufo image: {:?}", error),
+        };
+
+        let player_shot_sound = match audio::Source::new(ctx, "/player_shoot_sound.wav") {
+            Ok(sound) => sound,
+            Err(error) => panic!("Can't load player shot sound: {:?}", error),
+        };
+
+        let hit_sound = match audio::Source::new(ctx, "/hit.wav") {
+            Ok(sound) => sound,
+            Err(error) => panic!("Can't load hit shot sound: {:?}", error),
+        };
+
+        let extra_life_sound = match audio::Source::new(ctx, "/extra_life.wav") {
+            Ok(sound) => sound,
+            Err(error) => panic!("Can't load extra life sound: {:?}", error),
+        };
+
+        let powerup_images = POWERUP_TYPES.iter()
+            .map(|powerup_type| graphics::Image::from_color(ctx, POWERUP_SIZE.x as u32, POWERUP_SIZE.y as u32, Some(powerup_type.color())))
+            .collect();
+
+        Assets {
+            player_image,
+            player_bullet_image,
+            enemy_bullet_slow_image,
+            enemy_bullet_fast_image,
+            shield_image,
+            ufo_image,
+            player_shot_sound,
+            hit_sound,
+            extra_life_sound,
+            enemie_images,
+            enemie_damaged_images,
+            powerup_images,
+            font: BitmapFont::new(ctx),
+            pixel_image: graphics::Image::from_color(ctx, 1, 1, Some(graphics::Color::WHITE)),
+        }
+    }
+
+    pub fn set_sound_volume(&mut self, volume: f32) {
+        self.player_shot_sound.set_volume(volume);
+        self.hit_sound.set_volume(volume);
+        self.extra_life_sound.set_volume(volume);
+    }
+
+    pub fn actor_image(&self, actor: &Actor) -> &graphics::Image {
+        match actor.tag {
+            ActorType::Player => &self.player_image,
+            ActorType::PlayerBullet => &self.player_bullet_image,
+            ActorType::EnemyBulletSlow => &self.enemy_bullet_slow_image,
+            ActorType::EnemyBulletFast => &self.enemy_bullet_fast_image,
+            ActorType::EnemyBulletZigZag => &self.enemy_bullet_slow_image,
+            ActorType::EnemyBulletSquiggly => &self.enemy_bullet_slow_image,
+            ActorType::EnemyBulletPlunger => &self.enemy_bullet_fast_image,
+            ActorType::EnemyBulletRolling => &self.enemy_bullet_slow_image,
+            ActorType::EnemyA => &self.enemie_images[0],
+            ActorType::EnemyB => &self.enemie_images[1],
+            ActorType::EnemyC => &self.enemie_images[2],
+            ActorType::Ufo => &self.ufo_image,
+            ActorType::Shield => &self.shield_image,
+            ActorType::Powerup(powerup_type) => &self.powerup_images[powerup_type as usize],
+        }
+    }
+
+    pub fn enemy_image(&self, actor: &Actor, damaged: bool) -> &graphics::Image {
+        if damaged {
+            match actor.tag {
+                ActorType::EnemyA => return &self.enemie_damaged_images[0],
+                ActorType::EnemyB => return &self.enemie_damaged_images[1],
+                ActorType::EnemyC => return &self.enemie_damaged_images[2],
+                _ => ()
+            }
+        }
+
+        self.actor_image(actor)
+    }
+}
+
+//Loads a png and knocks holes into it, used for armoured enemies that took a hit
+fn load_damaged_image(ctx: &mut Context, path: &str) -> GameResult<graphics::Image> {
+    let file = ctx.fs.open(path)?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(|error| GameError::ResourceLoadError(format!("{:?}", error)))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|error| GameError::ResourceLoadError(format!("{:?}", error)))?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let mut pixels: Vec<u8> = match info.color_type {
+        png::ColorType::Rgba => buffer.to_vec(),
+        png::ColorType::Rgb => buffer.chunks(3).flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks(2).flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|gray| [*gray, *gray, *gray, 255]).collect(),
+        png::ColorType::Indexed => return Err(GameError::ResourceLoadError(format!("Unexpanded indexed png: {}", path))),
+    };
+
+    let width = info.width as usize;
+
+    for (i, pixel) in pixels.chunks_mut(4).enumerate() {
+        let (x, y) = (i % width, i / width);
+
+        if (x / 3 * 7 + y / 3 * 13) % 5 == 0 {
+            pixel[3] = 0;
+        }
+    }
+
+    Ok(graphics::Image::from_pixels(ctx, &pixels, graphics::ImageFormat::Rgba8UnormSrgb, info.width, info.height))
+}
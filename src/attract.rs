@@ -0,0 +1,219 @@
+use ggez::event;
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::{Context, GameResult};
+
+use crate::actor::ActorType;
+use crate::assets::Assets;
+use crate::bot::{bot_input, Autopilot};
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::hud::format_score;
+use crate::leaderboard::{leaderboard_text, LeaderboardClient};
+use crate::menu::draw_main_menu;
+use crate::replay::{create_replay_player, load_demo_replay, prepare_replay_game};
+use crate::settings::{draw_color_filter, draw_settings_menu};
+
+//Seconds without a key press on the title screen before the attract mode starts
+pub const ATTRACT_IDLE_TIME : f32 = 10.0;
+pub const ATTRACT_SCREEN_TIME : f32 = 6.0;
+//The demo game is cut short after this many seconds
+pub const DEMO_TIME : f32 = 45.0;
+pub const ATTRACT_TEXT_SIZE : f32 = 16.0;
+pub const ATTRACT_TITLE_SIZE : f32 = 32.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttractScreen {
+    Logo,
+    //Points of every enemy
+    ScoreTable,
+    HighScores,
+    Demo,
+}
+
+impl AttractScreen {
+    pub fn next(&self) -> AttractScreen {
+        match self {
+            AttractScreen::Logo => AttractScreen::ScoreTable,
+            AttractScreen::ScoreTable => AttractScreen::HighScores,
+            AttractScreen::HighScores => AttractScreen::Demo,
+            AttractScreen::Demo => AttractScreen::Logo,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AttractMode {
+    //None while the title menu is shown
+    pub screen: Option<AttractScreen>,
+    //Idle time on the title menu, then time spent on the current screen
+    pub timer: f32,
+    pub demo: Option<Box<GameState>>,
+}
+
+impl AttractMode {
+    pub fn is_running(&self) -> bool {
+        self.screen.is_some()
+    }
+
+    //Back to the title menu
+    pub fn stop(&mut self) {
+        self.screen = None;
+        self.timer = 0.0;
+        self.demo = None;
+    }
+
+    pub fn reset_idle(&mut self) {
+        self.timer = 0.0;
+    }
+
+    fn show(&mut self, ctx: &mut Context, screen: AttractScreen, leaderboard: &mut LeaderboardClient, max_score: u32) -> GameResult {
+        self.screen = Some(screen);
+        self.timer = 0.0;
+        self.demo = None;
+
+        match screen {
+            AttractScreen::HighScores => leaderboard.refresh(),
+            AttractScreen::Demo => self.start_demo(ctx, max_score)?,
+            _ => ()
+        }
+
+        Ok(())
+    }
+
+    fn start_demo(&mut self, ctx: &mut Context, max_score: u32) -> GameResult {
+        let mut demo = GameState::new_headless(ctx)?;
+        demo.set_max_score(max_score);
+
+        //Inputs of the best recorded run, the bot plays when there is none
+        demo.autopilot = Some(Autopilot::Bot(bot_input));
+
+        if let Some(replay) = load_demo_replay(ctx) {
+            if prepare_replay_game(&mut demo, &replay).is_ok() {
+                demo.autopilot = Some(Autopilot::Replay(create_replay_player(replay)));
+            }
+        }
+
+        self.demo = Some(Box::new(demo));
+        Ok(())
+    }
+
+    //Returns true when the demo game is over
+    fn update_demo(&mut self, ctx: &Context, delta_time: f32) -> GameResult<bool> {
+        let demo = match &mut self.demo {
+            Some(demo) => demo,
+            None => return Ok(true)
+        };
+
+        demo.timestep.advance(delta_time);
+
+        while demo.timestep.next_step() {
+            demo.step(ctx, demo.timestep.step_time)?;
+
+            //A replay out of inputs leaves the demo without autopilot
+            if demo.finished_replay.is_some() || demo.autopilot.is_none() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    //max_score is the high score of the host game, shown by the demo
+    pub fn update(&mut self, ctx: &mut Context, leaderboard: &mut LeaderboardClient, max_score: u32) -> GameResult {
+        let delta_time = ctx.time.delta().as_secs_f32();
+        self.timer += delta_time;
+
+        match self.screen {
+            None if self.timer > ATTRACT_IDLE_TIME => self.show(ctx, AttractScreen::Logo, leaderboard, max_score)?,
+            None => (),
+            Some(AttractScreen::Demo) => {
+                let demo_over = self.update_demo(ctx, delta_time)?;
+
+                if demo_over || self.timer > DEMO_TIME {
+                    self.show(ctx, AttractScreen::Logo, leaderboard, max_score)?;
+                }
+            },
+            Some(screen) if self.timer > ATTRACT_SCREEN_TIME => self.show(ctx, screen.next(), leaderboard, max_score)?,
+            Some(_) => ()
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_centered(canvas: &mut graphics::Canvas, assets: &Assets, text: &str, y: f32, size: f32, window_size: Vec2) {
+    let dimensions = BitmapFont::text_dimensions(text, size);
+    assets.font.draw(canvas, text, Vec2::new((window_size.x - dimensions.x) / 2.0, y), size, Color::WHITE);
+}
+
+fn draw_score_table(canvas: &mut graphics::Canvas, game: &GameState) {
+    let assets = &game.assets;
+    let window_size = game.window.size;
+
+    draw_centered(canvas, assets, "*SCORE ADVANCE TABLE*", window_size.y / 4.0, ATTRACT_TEXT_SIZE, window_size);
+
+    let score = |tag: ActorType| game.enemy_definitions.get(tag).map_or(0, |definition| definition.score);
+    let rows = [
+        (&assets.ufo_image, String::from("= ? MYSTERY")),
+        (&assets.enemie_images[2], format!("= {} POINTS", score(ActorType::EnemyC))),
+        (&assets.enemie_images[1], format!("= {} POINTS", score(ActorType::EnemyB))),
+        (&assets.enemie_images[0], format!("= {} POINTS", score(ActorType::EnemyA))),
+    ];
+
+    let mut y = window_size.y / 4.0 + ATTRACT_TEXT_SIZE * 4.0;
+
+    for (image, text) in rows {
+        let text_dimensions = BitmapFont::text_dimensions(&text, ATTRACT_TEXT_SIZE);
+        let image_size = Vec2::new(image.width() as f32, image.height() as f32) * 0.7;
+        let left = (window_size.x - image_size.x - text_dimensions.x - ATTRACT_TEXT_SIZE) / 2.0;
+
+        canvas.draw(image, graphics::DrawParam::new().dest(Vec2::new(left, y)).scale(Vec2::splat(0.7)));
+        assets.font.draw(canvas, &text, Vec2::new(left + image_size.x + ATTRACT_TEXT_SIZE, y + (image_size.y - text_dimensions.y) / 2.0), ATTRACT_TEXT_SIZE, Color::WHITE);
+
+        y += image_size.y.max(text_dimensions.y) + ATTRACT_TEXT_SIZE * 1.5;
+    }
+}
+
+fn draw_high_scores(canvas: &mut graphics::Canvas, game: &GameState) {
+    let window_size = game.window.size;
+    let text = format!("HI-SCORE {}\n\n{}", format_score(game.max_score), leaderboard_text(&game.leaderboard));
+
+    draw_centered(canvas, &game.assets, "HIGH SCORES", window_size.y / 4.0, ATTRACT_TITLE_SIZE, window_size);
+
+    let dimensions = BitmapFont::text_dimensions(&text, ATTRACT_TEXT_SIZE);
+    game.assets.font.draw(canvas, &text, Vec2::new((window_size.x - dimensions.x) / 2.0, window_size.y / 4.0 + ATTRACT_TITLE_SIZE * 3.0), ATTRACT_TEXT_SIZE, Color::WHITE);
+}
+
+//The title menu, or the attract mode screen playing over it
+pub fn draw_title_screen(ctx: &mut Context, game: &mut GameState) -> GameResult {
+    let window_size = game.window.size;
+
+    let mut canvas = match (game.attract.screen, &mut game.attract.demo) {
+        (Some(AttractScreen::Demo), Some(demo)) => {
+            //The demo game draws its own frame, the banner goes over it
+            demo.window.target = game.window.target.clone();
+            demo.settings.cellophane = game.settings.cellophane;
+            event::EventHandler::draw(demo.as_mut(), ctx)?;
+            game.window.canvas(ctx, None)
+        },
+        _ => game.window.canvas(ctx, Some(Color::BLACK))
+    };
+
+    canvas.set_sampler(graphics::Sampler::nearest_clamp());
+
+    match game.attract.screen {
+        None if game.settings_menu.open => draw_settings_menu(&mut canvas, &game.assets, &game.settings_menu, &game.settings, window_size),
+        None => draw_main_menu(&mut canvas, &game.assets, &game.menu, window_size),
+        Some(AttractScreen::Logo) => {
+            draw_centered(&mut canvas, &game.assets, "GALACTIC STRIKE", window_size.y / 3.0, ATTRACT_TITLE_SIZE, window_size);
+            draw_centered(&mut canvas, &game.assets, "PRESS ANY KEY", window_size.y * 2.0 / 3.0, ATTRACT_TEXT_SIZE, window_size);
+        },
+        Some(AttractScreen::ScoreTable) => draw_score_table(&mut canvas, game),
+        Some(AttractScreen::HighScores) => draw_high_scores(&mut canvas, game),
+        Some(AttractScreen::Demo) => draw_centered(&mut canvas, &game.assets, "DEMO - PRESS ANY KEY", window_size.y * 2.0 / 3.0, ATTRACT_TEXT_SIZE, window_size)
+    }
+
+    draw_color_filter(&mut canvas, game.settings.color_filter, window_size);
+    canvas.finish(ctx)
+}
@@ -0,0 +1,16 @@
+use ggez::audio::SoundSource;
+use ggez::{Context, GameResult};
+
+use crate::assets::Assets;
+use crate::events::GameEvent;
+
+pub fn play_event_sound(ctx: &Context, assets: &mut Assets, event: &GameEvent) -> GameResult {
+    match event {
+        GameEvent::PlayerShot{ .. } => assets.player_shot_sound.play(ctx)?,
+        GameEvent::EnemyDamaged{ .. } | GameEvent::EnemyKilled{ .. } | GameEvent::BossDamaged{ .. } => assets.hit_sound.play(ctx)?,
+        GameEvent::ExtraLife => assets.extra_life_sound.play(ctx)?,
+        _ => ()
+    }
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+//! Local stand-in for the online leaderboard.
+//!
+//! Scores are kept in a json file and every submission is checked by playing
+//! its replay again, a replay that doesn't reach the claimed score is rejected.
+//!
+//! Usage: `leaderboard_server [address] [scores file]`
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use ggez::{Context, GameResult};
+
+use galactic_strike::game::WORLD_SIZE;
+use galactic_strike::leaderboard::{LeaderboardEntry, Submission, LEADERBOARD_SIZE};
+use galactic_strike::replay::simulate_replay;
+use galactic_strike::ruleset::{Difficulty, Ruleset};
+use galactic_strike::scoring::ScoringMode;
+use galactic_strike::timestep::DEFAULT_SIMULATION_RATE;
+
+pub const DEFAULT_ADDRESS : &str = "127.0.0.1:7878";
+pub const DEFAULT_SCORES_FILE : &str = "leaderboard.json";
+pub const MAX_STORED_SCORES : usize = 100;
+pub const MAX_NAME_LENGTH : usize = 12;
+pub const MAX_BODY_SIZE : usize = 16 * 1024 * 1024;
+//Request line and headers together, the client only sends a handful of short ones
+pub const MAX_HEADER_SIZE : usize = 8 * 1024;
+//The server handles one connection at a time, a client that stalls must not hold it
+pub const REQUEST_TIMEOUT : Duration = Duration::from_secs(5);
+//Nine hours at 60 steps a second, longer replays would block the server while they are simulated
+pub const MAX_REPLAY_STEPS : u64 = 2_000_000;
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+//Reads one line without letting the request line and headers grow past MAX_HEADER_SIZE
+fn read_header_line(reader: &mut BufReader<&TcpStream>, header_size: &mut usize) -> Result<String, String> {
+    let mut line = String::new();
+    let limit = (MAX_HEADER_SIZE - *header_size) as u64;
+    reader.by_ref().take(limit).read_line(&mut line).map_err(|error| error.to_string())?;
+    *header_size += line.len();
+
+    if !line.ends_with('\n') {
+        return Err(String::from("headers too large or cut short"));
+    }
+
+    Ok(line)
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut header_size = 0;
+
+    let request_line = read_header_line(&mut reader, &mut header_size)?;
+
+    let mut words = request_line.split_whitespace();
+    let method = words.next().ok_or("empty request")?.to_string();
+    let path = words.next().ok_or("request without a path")?.to_string();
+
+    let mut content_length = 0;
+
+    loop {
+        let header = read_header_line(&mut reader, &mut header_size)?;
+
+        if header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| "bad content length")?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Err(String::from("body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|error| error.to_string())?;
+
+    Ok(Request {
+        method,
+        path,
+        body: String::from_utf8(body).map_err(|error| error.to_string())?,
+    })
+}
+
+fn write_response(mut stream: &TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error"
+    };
+
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, body.len(), body)
+}
+
+fn json_message(message: &str) -> String {
+    serde_json::to_string(message).unwrap_or_default()
+}
+
+fn load_scores(path: &str) -> Vec<LeaderboardEntry> {
+    std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+fn save_scores(path: &str, scores: &[LeaderboardEntry]) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(scores)?)
+}
+
+fn top_scores(scores: &[LeaderboardEntry], query: &str) -> (u16, String) {
+    let limit = query.split('&')
+        .find_map(|pair| pair.strip_prefix("limit="))
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(LEADERBOARD_SIZE);
+
+    let top = &scores[..limit.min(scores.len())];
+    (200, serde_json::to_string(top).unwrap_or_default())
+}
+
+fn submit_score(ctx: &mut Context, scores: &mut Vec<LeaderboardEntry>, scores_file: &str, body: &str) -> (u16, String) {
+    let submission: Submission = match serde_json::from_str(body) {
+        Ok(submission) => submission,
+        Err(error) => return (400, json_message(&format!("bad submission: {}", error)))
+    };
+
+    if submission.score != submission.replay.score {
+        return (422, json_message("score doesn't match the replay"));
+    }
+
+    let steps: u64 = submission.replay.inputs.iter().map(|(_, steps)| *steps as u64).sum();
+
+    if steps > MAX_REPLAY_STEPS {
+        return (422, json_message(&format!("replay too long: {} steps, at most {}", steps, MAX_REPLAY_STEPS)));
+    }
+
+    //Scores are only ranked against runs played at the same rate and under the same rules
+    if submission.replay.simulation_rate != DEFAULT_SIMULATION_RATE {
+        return (422, json_message(&format!("replay runs at {} steps a second, not {}", submission.replay.simulation_rate, DEFAULT_SIMULATION_RATE)));
+    }
+
+    if submission.replay.ruleset != Ruleset::default()
+        || submission.replay.difficulty != Difficulty::default()
+        || submission.replay.scoring_mode != ScoringMode::default() {
+        return (422, json_message("only runs with the default ruleset, difficulty and scoring are ranked"));
+    }
+
+    match simulate_replay(ctx, &submission.replay) {
+        Ok(score) if score == submission.score => (),
+        Ok(score) => return (422, json_message(&format!("replay scores {} not {}", score, submission.score))),
+        Err(error) => return (422, json_message(&format!("invalid replay: {}", error)))
+    }
+
+    let mut name: String = submission.name.trim().chars().take(MAX_NAME_LENGTH).collect();
+
+    if name.is_empty() {
+        name = String::from("ANONYMOUS");
+    }
+
+    println!("{} scored {}", name, submission.score);
+
+    scores.push(LeaderboardEntry { name, score: submission.score });
+    scores.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+    scores.truncate(MAX_STORED_SCORES);
+
+    match save_scores(scores_file, scores) {
+        Ok(()) => (200, json_message("ok")),
+        Err(error) => (500, json_message(&format!("can't save the scores: {}", error)))
+    }
+}
+
+fn handle_connection(ctx: &mut Context, stream: &TcpStream, scores: &mut Vec<LeaderboardEntry>, scores_file: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let request = match read_request(stream) {
+        Ok(request) => request,
+        Err(error) => return write_response(stream, 400, &json_message(&error))
+    };
+
+    let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+
+    let (status, body) = match (request.method.as_str(), path) {
+        ("GET", "/scores") => top_scores(scores, query),
+        ("POST", "/scores") => submit_score(ctx, scores, scores_file, &request.body),
+        _ => (404, json_message("not found"))
+    };
+
+    write_response(stream, status, &body)
+}
+
+pub fn main() -> GameResult {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| String::from(DEFAULT_ADDRESS));
+    let scores_file = args.next().unwrap_or_else(|| String::from(DEFAULT_SCORES_FILE));
+
+    //The replays are played in a hidden window of the same size as the game one
+    let (mut ctx, _events_loop) = ggez::ContextBuilder::new("galactic_strike_server", "Abbion")
+    .window_setup(ggez::conf::WindowSetup::default().title("Galactic strike leaderboard"))
+    .window_mode(ggez::conf::WindowMode::default().dimensions(WORLD_SIZE.x, WORLD_SIZE.y).visible(false))
+    .build()?;
+
+    let mut scores = load_scores(&scores_file);
+    let listener = TcpListener::bind(&address)?;
+    println!("leaderboard listening on {}, {} scores in {}", address, scores.len(), scores_file);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("connection failed: {}", error);
+                continue;
+            }
+        };
+
+        if let Err(error) = handle_connection(&mut ctx, &stream, &mut scores, &scores_file) {
+            eprintln!("request failed: {}", error);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Color, Rect};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::actor::{create_bullet, world_to_screen_coords, Actor, ActorType};
+use crate::assets::Assets;
+use crate::collision::bullet_hits_rect;
+use crate::enemies::{create_enemy, EnemyDefinitions, ENEMY_SCALE};
+use crate::rng::game_rng;
+
+pub const BOSS_WAVE_INTERVAL : u32 = 4;
+pub const BOSS_LIFE : f32 = 60.0;
+pub const BOSS_WING_LIFE : f32 = 10.0;
+//Damage multiplier of hits on the core
+pub const BOSS_CORE_DAMAGE : f32 = 2.0;
+pub const BOSS_SPEED : f32 = 80.0;
+pub const BOSS_CORE_SCALE : f32 = 2.0;
+pub const BOSS_WING_SCALE : f32 = 1.3;
+pub const BOSS_SCORE : u32 = 3000;
+pub const BOSS_PHASE_THRESHOLDS : [f32; 2] = [0.66, 0.33];
+pub const BOSS_ATTACK_TIMES : [f32; 3] = [2.5, 2.0, 1.5];
+pub const BOSS_CURTAIN_BULLETS : u32 = 12;
+pub const BOSS_LASER_WIDTH : f32 = 14.0;
+pub const BOSS_LASER_TIME : f32 = 2.5;
+pub const BOSS_LASER_SPEED : f32 = 250.0;
+pub const BOSS_MINIONS : u32 = 3;
+pub const BOSS_HP_BAR_HEIGHT : f32 = 8.0;
+pub const BOSS_HP_BAR_TOP : f32 = 64.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BossAttack {
+    //Row of bullets across the screen with a single gap
+    Curtain,
+    //Vertical beam sweeping toward the player
+    LaserSweep,
+    SpawnMinions,
+}
+
+//Collidable piece of the boss, positioned relative to the boss center
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossPart {
+    pub actor: Actor,
+    pub offset: Vec2,
+    pub weak_point: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossLaser {
+    pub x: f32,
+    pub direction: f32,
+    pub time_left: f32,
+    pub hit_player: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {
+    pub position: Vec2,
+    pub direction: f32,
+    pub parts: Vec<BossPart>,
+    pub hp: f32,
+    pub phase: usize,
+    pub time_to_attack: f32,
+    pub laser: Option<BossLaser>,
+}
+
+fn create_boss_part(tag: ActorType, scale: f32, assets: &Assets, offset: Vec2, hp: f32, weak_point: bool) -> BossPart {
+    let mut actor = create_enemy();
+    actor.tag = tag;
+
+    let image = assets.actor_image(&actor);
+    actor.scale = Vec2::new(scale, scale);
+    actor.size = Vec2::new(image.width() as f32 * scale, image.height() as f32 * scale);
+    actor.hp = hp;
+
+    BossPart { actor, offset, weak_point }
+}
+
+pub fn is_boss_wave(wave: u32) -> bool {
+    wave > 0 && wave.is_multiple_of(BOSS_WAVE_INTERVAL)
+}
+
+pub fn create_boss(assets: &Assets, window_size: Vec2) -> Boss {
+    let core = create_boss_part(ActorType::EnemyC, BOSS_CORE_SCALE, assets, Vec2::ZERO, BOSS_LIFE, true);
+    let wing_offset = core.actor.size.x * 0.9;
+
+    let mut boss = Boss {
+        position: Vec2::new(0.0, -window_size.y / 3.5),
+        direction: 1.0,
+        parts: vec![
+            create_boss_part(ActorType::EnemyB, BOSS_WING_SCALE, assets, Vec2::new(-wing_offset, 10.0), BOSS_WING_LIFE, false),
+            core,
+            create_boss_part(ActorType::EnemyB, BOSS_WING_SCALE, assets, Vec2::new(wing_offset, 10.0), BOSS_WING_LIFE, false),
+        ],
+        hp: BOSS_LIFE,
+        phase: 0,
+        time_to_attack: BOSS_ATTACK_TIMES[0],
+        laser: None,
+    };
+
+    update_boss_parts(&mut boss);
+    boss
+}
+
+fn update_boss_parts(boss: &mut Boss) {
+    for part in &mut boss.parts {
+        part.actor.position = boss.position + part.offset;
+    }
+}
+
+fn boss_phase(hp: f32) -> usize {
+    let ratio = hp / BOSS_LIFE;
+    BOSS_PHASE_THRESHOLDS.iter().filter(|threshold| ratio <= **threshold).count()
+}
+
+pub fn boss_bounds(boss: &Boss) -> Rect {
+    let mut bounds = boss.parts[0].actor.get_rect();
+
+    for part in boss.parts.iter().skip(1) {
+        bounds = bounds.combine_with(part.actor.get_rect());
+    }
+
+    bounds
+}
+
+pub fn boss_laser_rect(boss: &Boss, laser: &BossLaser, window_size: Vec2) -> Rect {
+    let top = boss.position.y;
+    Rect::new(laser.x - BOSS_LASER_WIDTH / 2.0, top, BOSS_LASER_WIDTH, window_size.y / 2.0 - top)
+}
+
+fn boss_attacks(phase: usize) -> &'static [BossAttack] {
+    match phase {
+        0 => &[BossAttack::Curtain],
+        1 => &[BossAttack::Curtain, BossAttack::LaserSweep],
+        _ => &[BossAttack::Curtain, BossAttack::LaserSweep, BossAttack::SpawnMinions],
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_attack(boss: &mut Boss, attack: BossAttack, assets: &Assets, definitions: &EnemyDefinitions, enemy_bullets: &mut Vec<Actor>, divers: &mut Vec<Actor>, target: Vec2, window_size: Vec2) {
+    let mut rng = game_rng();
+
+    match attack {
+        BossAttack::Curtain => {
+            let gap = rng.gen_range(0..BOSS_CURTAIN_BULLETS);
+            let spacing = window_size.x / BOSS_CURTAIN_BULLETS as f32;
+
+            for i in 0..BOSS_CURTAIN_BULLETS {
+                if i == gap || i == (gap + 1) % BOSS_CURTAIN_BULLETS {
+                    continue;
+                }
+
+                let mut bullet = create_bullet(ActorType::EnemyBulletSlow);
+                bullet.position = Vec2::new(-window_size.x / 2.0 + spacing * (i as f32 + 0.5), boss.position.y);
+                bullet.direction.y = 1.0;
+                enemy_bullets.push(bullet);
+            }
+        },
+        BossAttack::LaserSweep => {
+            boss.laser = Some(BossLaser {
+                x: boss.position.x,
+                direction: if target.x < boss.position.x { -1.0 } else { 1.0 },
+                time_left: BOSS_LASER_TIME,
+                hit_player: false,
+            });
+        },
+        BossAttack::SpawnMinions => {
+            let minion_size = Vec2::new(assets.enemie_images[0].width() as f32, assets.enemie_images[0].height() as f32) * ENEMY_SCALE;
+
+            for i in 0..BOSS_MINIONS {
+                let mut minion = create_enemy();
+                minion.tag = ActorType::EnemyA;
+                minion.size = minion_size;
+                minion.scale = Vec2::new(ENEMY_SCALE, ENEMY_SCALE);
+                minion.position = boss.position + Vec2::new((i as f32 - (BOSS_MINIONS - 1) as f32 / 2.0) * minion_size.x * 1.5, minion_size.y);
+                minion.direction = (target - minion.position).normalize_or_zero();
+
+                if let Some(definition) = definitions.get(ActorType::EnemyA) {
+                    minion.hp = definition.hp;
+                }
+
+                divers.push(minion);
+            }
+        }
+    }
+}
+
+//Moves the boss and runs its attacks, returns true when the laser hit the player
+#[allow(clippy::too_many_arguments)]
+pub fn update_boss(boss: &mut Boss, assets: &Assets, definitions: &EnemyDefinitions, enemy_bullets: &mut Vec<Actor>, divers: &mut Vec<Actor>, player: &Actor, window_size: Vec2, delta_time: f32) -> bool {
+    let speed = BOSS_SPEED * (1.0 + boss.phase as f32 * 0.5);
+    boss.position.x += boss.direction * speed * delta_time;
+    update_boss_parts(boss);
+
+    let bounds = boss_bounds(boss);
+    if bounds.right() > window_size.x / 2.0 {
+        boss.direction = -1.0;
+    }
+    else if bounds.left() < -window_size.x / 2.0 {
+        boss.direction = 1.0;
+    }
+
+    let phase = boss_phase(boss.hp);
+    if phase != boss.phase {
+        boss.phase = phase;
+        boss.time_to_attack = 0.0;
+    }
+
+    let mut player_hit = false;
+
+    if let Some(laser) = &mut boss.laser {
+        laser.x += laser.direction * BOSS_LASER_SPEED * delta_time;
+        laser.time_left -= delta_time;
+    }
+
+    if let Some(laser) = &boss.laser {
+        if !laser.hit_player && boss_laser_rect(boss, laser, window_size).overlaps(&player.get_rect()) {
+            player_hit = true;
+        }
+    }
+
+    if let Some(laser) = &mut boss.laser {
+        laser.hit_player |= player_hit;
+
+        if laser.time_left <= 0.0 {
+            boss.laser = None;
+        }
+    }
+
+    boss.time_to_attack -= delta_time;
+
+    if boss.time_to_attack <= 0.0 && boss.laser.is_none() {
+        let attacks = boss_attacks(boss.phase);
+        let attack = attacks[game_rng().gen_range(0..attacks.len())];
+
+        start_attack(boss, attack, assets, definitions, enemy_bullets, divers, player.position, window_size);
+        boss.time_to_attack = BOSS_ATTACK_TIMES[boss.phase.min(BOSS_ATTACK_TIMES.len() - 1)];
+    }
+
+    player_hit
+}
+
+//Applies a player bullet hit to the part under the bullet, returns true if any part was hit
+pub fn damage_boss(boss: &mut Boss, bullet_points: &(Vec2, Vec2), damage: f32) -> bool {
+    for part in &mut boss.parts {
+        if !part.actor.is_alive() || !bullet_hits_rect(bullet_points, &part.actor.get_rect()) {
+            continue;
+        }
+
+        if part.weak_point {
+            boss.hp -= damage * BOSS_CORE_DAMAGE;
+        }
+        else {
+            part.actor.damage(damage);
+            boss.hp -= damage;
+        }
+
+        return true;
+    }
+
+    false
+}
+
+pub fn draw_boss(canvas: &mut graphics::Canvas, assets: &Assets, boss: &Boss, window_size: Vec2, alpha: f32) {
+    let phase_color = match boss.phase {
+        0 => Color::WHITE,
+        1 => Color::from_rgb(255, 200, 120),
+        _ => Color::from_rgb(255, 110, 110),
+    };
+
+    for part in &boss.parts {
+        if !part.actor.is_alive() {
+            continue;
+        }
+
+        let drawparams = graphics::DrawParam::new()
+            .dest(world_to_screen_coords(window_size.x, window_size.y, part.actor.draw_position(alpha)))
+            .scale(part.actor.scale)
+            .offset(Vec2::new(0.5, 0.5))
+            .color(phase_color);
+
+        canvas.draw(assets.actor_image(&part.actor), drawparams);
+    }
+
+    if let Some(laser) = &boss.laser {
+        let laser_rect = boss_laser_rect(boss, laser, window_size);
+        let drawparams = graphics::DrawParam::new()
+            .dest(world_to_screen_coords(window_size.x, window_size.y, laser_rect.point().into()))
+            .scale(Vec2::new(laser_rect.w, laser_rect.h))
+            .color(Color::from_rgba(255, 60, 60, 190));
+
+        canvas.draw(&assets.pixel_image, drawparams);
+    }
+
+    //Hp bar
+    let bar_width = window_size.x * 0.6;
+    let bar_position = Vec2::new((window_size.x - bar_width) / 2.0, BOSS_HP_BAR_TOP);
+    let fill = (boss.hp / BOSS_LIFE).clamp(0.0, 1.0);
+
+    canvas.draw(&assets.pixel_image, graphics::DrawParam::new()
+        .dest(bar_position)
+        .scale(Vec2::new(bar_width, BOSS_HP_BAR_HEIGHT))
+        .color(Color::from_rgb(60, 60, 60)));
+
+    canvas.draw(&assets.pixel_image, graphics::DrawParam::new()
+        .dest(bar_position)
+        .scale(Vec2::new(bar_width * fill, BOSS_HP_BAR_HEIGHT))
+        .color(phase_color));
+}
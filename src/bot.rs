@@ -0,0 +1,74 @@
+use crate::game::GameState;
+use crate::input::InputState;
+use crate::replay::ReplayPlayer;
+
+//Height above the ship where enemy bullets start being dodged
+pub const BOT_DODGE_DISTANCE : f32 = 150.0;
+//Horizontal distance to the target the ship stops at
+pub const BOT_AIM_TOLERANCE : f32 = 4.0;
+
+pub type Bot = fn(&GameState) -> InputState;
+
+//Bots that can be picked by name from the command line
+pub const BOTS : [(&str, Bot); 2] = [("hunter", bot_input), ("turret", turret_input)];
+
+pub fn find_bot(name: &str) -> Option<Bot> {
+    BOTS.iter().find(|(bot_name, _)| *bot_name == name).map(|(_, bot)| *bot)
+}
+
+//Plays a game in place of the player
+#[derive(Debug)]
+pub enum Autopilot {
+    Bot(Bot),
+    Replay(ReplayPlayer),
+}
+
+impl Autopilot {
+    //None once a replay has no input left
+    pub fn next_input(&mut self, game: &GameState) -> Option<InputState> {
+        match self {
+            Autopilot::Bot(bot) => Some(bot(game)),
+            Autopilot::Replay(player) => player.next_input(),
+        }
+    }
+}
+
+//Stays where it is and fires all the time, a stable baseline for soak tests
+pub fn turret_input(_game: &GameState) -> InputState {
+    InputState {
+        fire: true,
+        ..InputState::default()
+    }
+}
+
+//Input of the built-in player used by the attract mode demo
+pub fn bot_input(game: &GameState) -> InputState {
+    let player = &game.player;
+
+    //The bullet coming down on the ship closest to hit it
+    let threat = game.enemy_bullets.iter()
+        .filter(|bullet| bullet.position.y < player.position.y && player.position.y - bullet.position.y < BOT_DODGE_DISTANCE)
+        .filter(|bullet| (bullet.position.x - player.position.x).abs() < player.size.x)
+        .max_by(|a, b| a.position.y.total_cmp(&b.position.y));
+
+    let boss_parts = game.boss.iter().flat_map(|boss| boss.parts.iter().map(|part| &part.actor));
+
+    let target_x = match threat {
+        Some(bullet) if bullet.position.x > player.position.x => player.position.x - player.size.x,
+        Some(_) => player.position.x + player.size.x,
+        None => game.enemies.iter()
+            .chain(game.divers.iter())
+            .chain(game.ufo.iter())
+            .chain(boss_parts)
+            .filter(|target| target.is_alive())
+            .map(|target| target.position.x)
+            .min_by(|a, b| (a - player.position.x).abs().total_cmp(&(b - player.position.x).abs()))
+            .unwrap_or(player.position.x)
+    };
+
+    InputState {
+        left: target_x < player.position.x - BOT_AIM_TOLERANCE,
+        right: target_x > player.position.x + BOT_AIM_TOLERANCE,
+        fire: threat.is_none() && (target_x - player.position.x).abs() < player.size.x / 2.0,
+    }
+}
@@ -0,0 +1,77 @@
+use ggez::glam::*;
+
+use crate::actor::{create_bullet, Actor, ActorType};
+use crate::enemies::{last_enemies_in_columns, EnemiesControler, ENEMY_START_TICK};
+
+pub const CLASSIC_SHOT_TIME : f32 = 0.8;
+pub const CLASSIC_MIN_TICK : f32 = 0.05;
+//Time of a formation step for every enemy left, a full formation of 55 steps as slow as the modern start tick
+pub const CLASSIC_TICK_PER_ENEMY : f32 = ENEMY_START_TICK / 55.0;
+
+//Arcade shots are fired in this order, only one of every type can be on screen
+pub const CLASSIC_SHOT_ROTATION : [ActorType; 3] = [ActorType::EnemyBulletRolling, ActorType::EnemyBulletPlunger, ActorType::EnemyBulletSquiggly];
+
+//Columns (counted from the left, starting at 1) the plunger and squiggly shots come from
+pub const PLUNGER_COLUMN_TABLE : [usize; 16] = [1, 7, 1, 1, 1, 4, 11, 1, 6, 3, 1, 1, 11, 9, 2, 8];
+pub const SQUIGGLY_COLUMN_TABLE : [usize; 15] = [11, 1, 6, 3, 1, 1, 11, 9, 2, 8, 2, 11, 4, 7, 10];
+
+//In the arcade one enemy moves per frame, so the fewer are left the faster the formation steps
+pub fn classic_tick_time(enemies_left: usize) -> f32 {
+    (enemies_left as f32 * CLASSIC_TICK_PER_ENEMY).max(CLASSIC_MIN_TICK)
+}
+
+fn next_table_column(table: &[usize], index: &mut usize) -> usize {
+    let column = table[*index % table.len()] - 1;
+    *index = (*index + 1) % table.len();
+    column
+}
+
+//Column of the bottom enemy closest to the player, the rolling shot is aimed this way
+fn closest_column(enemies: &[Actor], last_in_columns: &[i16], target_x: f32) -> Option<usize> {
+    last_in_columns.iter().enumerate()
+        .filter(|(_, index)| **index >= 0)
+        .min_by(|(_, a), (_, b)| {
+            let distance_a = (enemies[**a as usize].position.x - target_x).abs();
+            let distance_b = (enemies[**b as usize].position.x - target_x).abs();
+            distance_a.total_cmp(&distance_b)
+        })
+        .map(|(column, _)| column)
+}
+
+pub fn classic_enemies_shoot(enemies_controler: &mut EnemiesControler, enemies: &[Actor], enemy_bullets: &mut Vec<Actor>, target_x: f32, ufo_on_screen: bool, delta_time: f32) {
+    enemies_controler.time_from_last_shot += delta_time;
+
+    if enemies.is_empty() || enemies_controler.start_to_move || enemies_controler.time_from_last_shot < CLASSIC_SHOT_TIME {
+        return;
+    }
+
+    enemies_controler.time_from_last_shot = 0.0;
+
+    let shot_type = CLASSIC_SHOT_ROTATION[enemies_controler.classic_shot_index];
+    enemies_controler.classic_shot_index = (enemies_controler.classic_shot_index + 1) % CLASSIC_SHOT_ROTATION.len();
+
+    if enemy_bullets.iter().any(|bullet| bullet.tag == shot_type) {
+        return;
+    }
+
+    let last_in_columns = last_enemies_in_columns(enemies_controler);
+
+    let column = match shot_type {
+        ActorType::EnemyBulletRolling => closest_column(enemies, &last_in_columns, target_x),
+        //The plunger shot is not used when only one enemy is left
+        ActorType::EnemyBulletPlunger if enemies.len() > 1 => Some(next_table_column(&PLUNGER_COLUMN_TABLE, &mut enemies_controler.plunger_column_index)),
+        //The squiggly shot shares its timer with the ufo
+        ActorType::EnemyBulletSquiggly if !ufo_on_screen => Some(next_table_column(&SQUIGGLY_COLUMN_TABLE, &mut enemies_controler.squiggly_column_index)),
+        _ => None
+    };
+
+    let shooter = match column.map(|column| last_in_columns[column]) {
+        Some(index) if index >= 0 => &enemies[index as usize],
+        _ => return
+    };
+
+    let mut bullet = create_bullet(shot_type);
+    bullet.position = shooter.position + Vec2{x: 0.0, y: 35.0};
+    bullet.direction.y = 1.0;
+    enemy_bullets.push(bullet);
+}
@@ -0,0 +1,407 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ggez::{Context, GameError, GameResult};
+
+use crate::bot::{find_bot, Autopilot, BOTS};
+use crate::game::GameState;
+use crate::replay::{create_replay_player, load_replay_file, prepare_replay_game, save_replay_file};
+use crate::ruleset::{Difficulty, DIFFICULTIES};
+use crate::settings::{load_settings_before_build, load_settings_from_path, Settings};
+
+pub const GAME_ID : &str = "galactic_strike";
+pub const GAME_AUTHOR : &str = "Abbion";
+
+pub const USAGE : &str = "\
+Usage: galactic_strike [OPTIONS]
+       galactic_strike publish <address> [OPTIONS]
+       galactic_strike versus <local address> <remote address>
+       galactic_strike spectate <address>
+
+Options:
+  --seed <n>           Start the run from this seed
+  --replay <file>      Play a replay written by --record
+  --record <file>      Write the replay of the run to a file at the game over
+  --level <n>          Start at this wave
+  --config <file>      Read the settings from this file instead of settings.json
+  --fullscreen         Start in fullscreen
+  --window <w>x<h>     Size of the window
+  --headless           Simulate without drawing or sound, then print a summary
+  --ticks <n>          Ticks simulated by --headless, a replay plays to its end by default
+  --bot <name>         Let a bot play: hunter, turret
+  --difficulty <name>  easy, normal or hard
+  --resources <dir>    Also load the resources from this directory
+  --help               Show this help
+";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum LaunchMode {
+    #[default]
+    Play,
+    //Play while streaming the game to spectators
+    Publish(String),
+    Versus(String, String),
+    Spectate(String),
+}
+
+#[derive(Debug, Default)]
+pub struct LaunchOptions {
+    pub mode: LaunchMode,
+    pub seed: Option<u64>,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub level: Option<u32>,
+    pub config: Option<PathBuf>,
+    pub fullscreen: bool,
+    pub window: Option<(f32, f32)>,
+    pub headless: bool,
+    pub ticks: Option<u32>,
+    pub bot: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub resources: Option<PathBuf>,
+    pub help: bool,
+}
+
+fn next_value<'a>(args: &mut impl Iterator<Item = &'a str>, flag: &str) -> Result<&'a str, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", flag))
+}
+
+fn parse_number<T: FromStr>(value: &str, flag: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} expects a number, got {}", flag, value))
+}
+
+fn parse_window_size(value: &str) -> Result<(f32, f32), String> {
+    let size = value.split_once('x')
+        .and_then(|(width, height)| Some((width.parse::<u32>().ok()?, height.parse::<u32>().ok()?)));
+
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width as f32, height as f32)),
+        _ => Err(format!("--window expects <width>x<height>, got {}", value))
+    }
+}
+
+fn parse_difficulty(value: &str) -> Result<Difficulty, String> {
+    DIFFICULTIES.iter()
+        .find(|difficulty| difficulty.name().eq_ignore_ascii_case(value))
+        .copied()
+        .ok_or_else(|| format!("unknown difficulty {}, expected easy, normal or hard", value))
+}
+
+//The first argument is the program name
+pub fn parse_args(args: &[String]) -> Result<LaunchOptions, String> {
+    let mut options = LaunchOptions::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter().skip(1).map(String::as_str);
+
+    while let Some(arg) = args.next() {
+        match arg {
+            "--help" | "-h" => options.help = true,
+            "--seed" => options.seed = Some(parse_number(next_value(&mut args, arg)?, arg)?),
+            "--replay" => options.replay = Some(PathBuf::from(next_value(&mut args, arg)?)),
+            "--record" => options.record = Some(PathBuf::from(next_value(&mut args, arg)?)),
+            "--level" => options.level = Some(parse_number(next_value(&mut args, arg)?, arg)?),
+            "--config" => options.config = Some(PathBuf::from(next_value(&mut args, arg)?)),
+            "--fullscreen" => options.fullscreen = true,
+            "--window" => options.window = Some(parse_window_size(next_value(&mut args, arg)?)?),
+            "--headless" => options.headless = true,
+            "--ticks" => options.ticks = Some(parse_number(next_value(&mut args, arg)?, arg)?),
+            "--bot" => {
+                let name = next_value(&mut args, arg)?;
+
+                if find_bot(name).is_none() {
+                    let names: Vec<&str> = BOTS.iter().map(|(bot_name, _)| *bot_name).collect();
+                    return Err(format!("unknown bot {}, expected one of {}", name, names.join(", ")));
+                }
+
+                options.bot = Some(name.to_string());
+            },
+            "--difficulty" => options.difficulty = Some(parse_difficulty(next_value(&mut args, arg)?)?),
+            "--resources" => options.resources = Some(PathBuf::from(next_value(&mut args, arg)?)),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            _ => positional.push(arg)
+        }
+    }
+
+    options.mode = match positional.as_slice() {
+        [] => LaunchMode::Play,
+        ["publish", address] => LaunchMode::Publish(address.to_string()),
+        ["versus", local_address, remote_address] => LaunchMode::Versus(local_address.to_string(), remote_address.to_string()),
+        ["spectate", address] => LaunchMode::Spectate(address.to_string()),
+        _ => return Err(format!("unexpected arguments: {}", positional.join(" ")))
+    };
+
+    options.validate()?;
+    Ok(options)
+}
+
+impl LaunchOptions {
+    //Options that pick how the run is played, the title screen is skipped with any of them
+    fn run_options(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+
+        if self.seed.is_some() {
+            names.push("--seed");
+        }
+
+        if self.replay.is_some() {
+            names.push("--replay");
+        }
+
+        if self.level.is_some() {
+            names.push("--level");
+        }
+
+        if self.bot.is_some() {
+            names.push("--bot");
+        }
+
+        names
+    }
+
+    pub fn starts_run(&self) -> bool {
+        !self.run_options().is_empty()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.help {
+            return Ok(());
+        }
+
+        if let LaunchMode::Versus(..) | LaunchMode::Spectate(..) = self.mode {
+            let mut names = self.run_options();
+
+            if self.record.is_some() {
+                names.push("--record");
+            }
+
+            if self.headless {
+                names.push("--headless");
+            }
+
+            if self.difficulty.is_some() {
+                names.push("--difficulty");
+            }
+
+            if let Some(name) = names.first() {
+                return Err(format!("{} can't be used with versus or spectate", name));
+            }
+        }
+
+        //The replay brings its own seed and settings
+        if self.replay.is_some() {
+            for (used, name) in [(self.seed.is_some(), "--seed"), (self.level.is_some(), "--level"), (self.bot.is_some(), "--bot"), (self.difficulty.is_some(), "--difficulty")] {
+                if used {
+                    return Err(format!("{} can't be used with --replay", name));
+                }
+            }
+        }
+
+        if self.level.is_some() && self.record.is_some() {
+            return Err(String::from("--record can't be used with --level, replays start at the first wave"));
+        }
+
+        if self.ticks.is_some() && !self.headless {
+            return Err(String::from("--ticks needs --headless"));
+        }
+
+        if self.headless && self.ticks.is_none() && self.replay.is_none() {
+            return Err(String::from("--headless needs --ticks or --replay"));
+        }
+
+        if self.headless && matches!(self.mode, LaunchMode::Publish(_)) {
+            return Err(String::from("--headless can't be used with publish"));
+        }
+
+        Ok(())
+    }
+
+    //The settings file with the overrides of the command line, read before the window exists
+    pub fn settings(&self) -> Result<Settings, String> {
+        let mut settings = match &self.config {
+            Some(path) => load_settings_from_path(path)?,
+            None => load_settings_before_build(GAME_ID, GAME_AUTHOR)
+        };
+
+        if self.fullscreen {
+            settings.fullscreen = true;
+        }
+
+        if let Some(window) = self.window {
+            settings.resolution = window;
+        }
+
+        if let Some(difficulty) = self.difficulty {
+            settings.difficulty = difficulty;
+        }
+
+        Ok(settings)
+    }
+}
+
+//Applies the launch options to a freshly created game
+pub fn prepare_game(game: &mut GameState, options: &LaunchOptions, settings: Settings) -> GameResult {
+    game.settings = settings;
+    game.apply_settings();
+
+    if let Some(path) = &options.replay {
+        let replay = load_replay_file(path).map_err(GameError::CustomError)?;
+        prepare_replay_game(game, &replay).map_err(GameError::CustomError)?;
+        game.autopilot = Some(Autopilot::Replay(create_replay_player(replay)));
+    }
+
+    if let Some(seed) = options.seed {
+        game.start_replay_with_seed(seed);
+    }
+
+    if let Some(wave) = options.level {
+        game.skip_to_wave(wave);
+    }
+
+    if let Some(bot) = options.bot.as_deref().and_then(find_bot) {
+        game.autopilot = Some(Autopilot::Bot(bot));
+    }
+
+    game.record_path = options.record.clone();
+
+    if options.starts_run() {
+        game.menu.open = false;
+    }
+
+    Ok(())
+}
+
+//Runs the simulation as fast as possible for CI and soak tests, stops at the game over
+pub fn run_headless(ctx: &Context, game: &mut GameState, options: &LaunchOptions) -> GameResult {
+    let ticks = match (options.ticks, &game.autopilot) {
+        (Some(ticks), _) => ticks,
+        (None, Some(Autopilot::Replay(player))) => player.replay.steps(),
+        (None, _) => 0
+    };
+
+    let mut played = 0;
+    let mut finished = None;
+    //The game over resets the wave, the one reached is kept
+    let mut wave = game.wave;
+
+    while played < ticks {
+        game.step(ctx, game.timestep.step_time)?;
+        played += 1;
+
+        if let Some(replay) = game.finished_replay.take() {
+            finished = Some(replay);
+            break;
+        }
+
+        wave = game.wave;
+    }
+
+    let game_over = finished.is_some();
+    let score = finished.as_ref().map_or(game.score, |replay| replay.score);
+
+    if let Some(path) = &options.record {
+        //A run cut short by --ticks is saved up to its last tick
+        match finished.or_else(|| game.replay.finish(game.score)) {
+            Some(replay) => save_replay_file(path, &replay).map_err(GameError::CustomError)?,
+            None => eprintln!("the run can't be replayed, {} was not written", path.display())
+        }
+    }
+
+    println!("ticks {} score {} wave {} game over {}", played, score, wave, game_over);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<LaunchOptions, String> {
+        let args: Vec<String> = std::iter::once("galactic_strike").chain(line.split_whitespace()).map(String::from).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn rejected_combinations() {
+        let cases = [
+            ("--replay run.json --seed 3", "--seed can't be used with --replay"),
+            ("--replay run.json --level 2", "--level can't be used with --replay"),
+            ("--replay run.json --bot hunter", "--bot can't be used with --replay"),
+            ("--replay run.json --difficulty hard", "--difficulty can't be used with --replay"),
+            ("--level 3 --record run.json", "--record can't be used with --level, replays start at the first wave"),
+            ("--ticks 100", "--ticks needs --headless"),
+            ("--headless", "--headless needs --ticks or --replay"),
+            ("publish 127.0.0.1:9000 --headless --ticks 10", "--headless can't be used with publish"),
+            ("versus 127.0.0.1:7001 127.0.0.1:7002 --bot hunter", "--bot can't be used with versus or spectate"),
+            ("versus 127.0.0.1:7001 127.0.0.1:7002 --record run.json", "--record can't be used with versus or spectate"),
+            ("spectate 127.0.0.1:9000 --seed 1", "--seed can't be used with versus or spectate"),
+            ("spectate 127.0.0.1:9000 --difficulty easy", "--difficulty can't be used with versus or spectate"),
+        ];
+
+        for (line, error) in cases {
+            assert_eq!(parse(line).err().as_deref(), Some(error), "{}", line);
+        }
+    }
+
+    #[test]
+    fn rejected_values() {
+        let cases = [
+            ("--window 800", "--window expects <width>x<height>, got 800"),
+            ("--window 0x600", "--window expects <width>x<height>, got 0x600"),
+            ("--window widexhigh", "--window expects <width>x<height>, got widexhigh"),
+            ("--seed many", "--seed expects a number, got many"),
+            ("--ticks -5 --headless", "--ticks expects a number, got -5"),
+            ("--seed", "--seed needs a value"),
+            ("--bot camper", "unknown bot camper, expected one of hunter, turret"),
+            ("--difficulty insane", "unknown difficulty insane, expected easy, normal or hard"),
+            ("--turbo", "unknown option --turbo"),
+            ("publish", "unexpected arguments: publish"),
+            ("versus 127.0.0.1:7001", "unexpected arguments: versus 127.0.0.1:7001"),
+            ("play now", "unexpected arguments: play now"),
+        ];
+
+        for (line, error) in cases {
+            assert_eq!(parse(line).err().as_deref(), Some(error), "{}", line);
+        }
+    }
+
+    #[test]
+    fn positional_modes() {
+        let cases = [
+            ("", LaunchMode::Play),
+            ("--seed 4", LaunchMode::Play),
+            ("publish 0.0.0.0:9000", LaunchMode::Publish(String::from("0.0.0.0:9000"))),
+            ("versus 127.0.0.1:7001 127.0.0.1:7002", LaunchMode::Versus(String::from("127.0.0.1:7001"), String::from("127.0.0.1:7002"))),
+            ("spectate 10.0.0.2:9000 --fullscreen", LaunchMode::Spectate(String::from("10.0.0.2:9000"))),
+        ];
+
+        for (line, mode) in cases {
+            assert_eq!(parse(line).map(|options| options.mode), Ok(mode), "{}", line);
+        }
+    }
+
+    #[test]
+    fn accepted_options() {
+        let options = parse("--seed 7 --level 3 --bot turret --difficulty HARD --window 800x600 --fullscreen --headless --ticks 600").unwrap();
+
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.level, Some(3));
+        assert_eq!(options.bot.as_deref(), Some("turret"));
+        assert_eq!(options.difficulty, Some(Difficulty::Hard));
+        assert_eq!(options.window, Some((800.0, 600.0)));
+        assert!(options.fullscreen && options.headless);
+        assert_eq!(options.ticks, Some(600));
+        assert!(options.starts_run());
+
+        let options = parse("--headless --replay run.json --record copy.json").unwrap();
+        assert_eq!(options.replay, Some(PathBuf::from("run.json")));
+        assert_eq!(options.record, Some(PathBuf::from("copy.json")));
+        assert!(options.starts_run());
+
+        assert!(!parse("--record run.json --difficulty easy").unwrap().starts_run());
+    }
+
+    #[test]
+    fn help_skips_the_combination_checks() {
+        assert!(parse("--help --ticks 5").unwrap().help);
+        assert!(parse("spectate 127.0.0.1:9000 -h --bot hunter").unwrap().help);
+    }
+}
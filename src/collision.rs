@@ -0,0 +1,61 @@
+use ggez::glam::*;
+use ggez::graphics::Rect;
+
+use crate::actor::ActorType;
+
+//Damage dealt by every kind of hit that is not a player weapon hit
+#[derive(Debug, Copy, Clone)]
+pub struct CollisionRules {
+    //Enemy bullets and ramming enemies against the player
+    pub player_damage: f32,
+    //Any bullet against a shield
+    pub shield_damage: f32,
+    //Player bullet against enemy bullet, dealt to both of them
+    pub clash_damage: f32,
+    //Enemy bullets that keep flying after shooting down a player bullet
+    pub clash_survivors: &'static [ActorType],
+}
+
+impl Default for CollisionRules {
+    fn default() -> Self {
+        CollisionRules {
+            player_damage: 1.0,
+            shield_damage: 1.0,
+            clash_damage: 1.0,
+            clash_survivors: &[],
+        }
+    }
+}
+
+impl CollisionRules {
+    pub fn survives_clash(&self, bullet_type: ActorType) -> bool {
+        self.clash_survivors.contains(&bullet_type)
+    }
+}
+
+pub fn point_in_rect(point: &Vec2, rect: &Rect) -> bool {
+    if point.x > rect.left() && point.x < rect.right() &&
+       point.y < rect.bottom() && point.y > rect.top()
+    {
+       return true;
+    }
+
+    false
+}
+
+pub fn point_position_in_rect(point: &Vec2, rect: &Rect) -> Vec2 {
+    let left = point.x - rect.left();
+    let bottom = point.y - rect.bottom();
+    Vec2::new(left, bottom)
+}
+
+//Returns the top and the bottom center points of a bullet rect
+pub fn bullet_hit_points(bullet_rect: &Rect) -> (Vec2, Vec2) {
+    let bullet_top = Vec2::new((bullet_rect.left() + bullet_rect.right()) / 2.0, bullet_rect.top());
+    let bullet_down = Vec2::new((bullet_rect.left() + bullet_rect.right()) / 2.0, bullet_rect.bottom());
+    (bullet_top, bullet_down)
+}
+
+pub fn bullet_hits_rect(bullet_points: &(Vec2, Vec2), rect: &Rect) -> bool {
+    point_in_rect(&bullet_points.0, rect) | point_in_rect(&bullet_points.1, rect)
+}
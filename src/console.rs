@@ -0,0 +1,314 @@
+use std::io::Read;
+
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::Context;
+
+use crate::assets::Assets;
+use crate::boss::create_boss;
+use crate::enemies::get_enemies_rect;
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::powerups::{create_powerup, create_random_powerup, PowerupType, POWERUP_TYPES};
+use crate::rng::seed_game_rng;
+use crate::shields::create_shileds;
+use crate::ufo::create_ufo;
+
+//Commands run at startup, one per line, lines starting with # are comments
+pub const CONSOLE_SCRIPT_FILE : &str = "/console.txt";
+pub const CONSOLE_OUTPUT_LINES : usize = 8;
+pub const CONSOLE_TEXT_SIZE : f32 = 10.0;
+pub const CONSOLE_MARGIN : f32 = 8.0;
+//Slowest formation tick the console accepts, in seconds
+pub const MAX_CONSOLE_TICK : f32 = 10.0;
+
+#[derive(Debug, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    SpawnUfo,
+    SpawnBoss,
+    //None spawns a random powerup
+    SpawnPowerup(Option<PowerupType>),
+    //Formation row counted from 1 at the top
+    KillRow(usize),
+    KillAll,
+    SetLives(f32),
+    SetTick(f32),
+    God,
+    Wave(u32),
+    ShieldReset,
+    Seed(u64),
+}
+
+impl Console {
+    pub fn print(&mut self, line: String) {
+        self.output.push(line);
+
+        if self.output.len() > CONSOLE_OUTPUT_LINES {
+            self.output.remove(0);
+        }
+    }
+
+    pub fn type_character(&mut self, character: char) {
+        if !character.is_control() && character != '`' {
+            self.input.push(character);
+        }
+    }
+
+    pub fn erase_character(&mut self) {
+        self.input.pop();
+    }
+
+    //Takes the line typed so far
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.input);
+        self.print(format!("> {}", line));
+        line
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str, usage: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("usage: {}", usage))
+}
+
+pub fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let words: Vec<String> = line.split_whitespace().map(|word| word.to_lowercase()).collect();
+    let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+
+    match words.as_slice() {
+        ["spawn", "ufo"] => Ok(ConsoleCommand::SpawnUfo),
+        ["spawn", "boss"] => Ok(ConsoleCommand::SpawnBoss),
+        ["spawn", "powerup"] => Ok(ConsoleCommand::SpawnPowerup(None)),
+        ["spawn", "powerup", name] => POWERUP_TYPES.iter()
+            .find(|powerup_type| powerup_type.name().to_lowercase().replace(' ', "_") == *name)
+            .map(|powerup_type| ConsoleCommand::SpawnPowerup(Some(*powerup_type)))
+            .ok_or_else(|| format!("unknown powerup {}", name)),
+        ["kill", "all"] => Ok(ConsoleCommand::KillAll),
+        ["kill", "row", row] => parse_value(row, "kill row <1-5>").map(ConsoleCommand::KillRow),
+        ["set", "lives", lives] => parse_value(lives, "set lives <n>")
+            .and_then(|lives: f32| if lives.is_finite() { Ok(lives) } else { Err(String::from("usage: set lives <n>")) })
+            .map(ConsoleCommand::SetLives),
+        ["set", "tick", tick] => parse_value(tick, "set tick <seconds>")
+            .and_then(|tick: f32| if tick > 0.0 && tick <= MAX_CONSOLE_TICK {
+                Ok(tick)
+            } else {
+                Err(format!("tick must be above 0 and at most {} seconds", MAX_CONSOLE_TICK))
+            })
+            .map(ConsoleCommand::SetTick),
+        ["god"] => Ok(ConsoleCommand::God),
+        ["wave", wave] => parse_value(wave, "wave <n>").map(ConsoleCommand::Wave),
+        ["shield", "reset"] => Ok(ConsoleCommand::ShieldReset),
+        ["seed", seed] => parse_value(seed, "seed <n>").map(ConsoleCommand::Seed),
+        [] => Err(String::new()),
+        _ => Err(format!("unknown command {}", line.trim()))
+    }
+}
+
+//Kills every enemy of a formation row without awarding the score
+fn kill_row(game: &mut GameState, row: usize) -> usize {
+    let flags = &mut game.enemies_controler.enemies_alive_flags;
+
+    if row == 0 || row > flags.len() {
+        return 0;
+    }
+
+    let start: usize = flags.iter().take(row - 1).map(|alive_row| alive_row.iter().filter(|alive| **alive).count()).sum();
+    let count = flags[row - 1].iter().filter(|alive| **alive).count();
+
+    for alive in &mut flags[row - 1] {
+        *alive = false;
+    }
+
+    for enemie in game.enemies.iter_mut().skip(start).take(count) {
+        enemie.kill();
+    }
+
+    count
+}
+
+//Returns the message printed in the console
+pub fn execute_command(game: &mut GameState, command: ConsoleCommand) -> String {
+    match command {
+        ConsoleCommand::SpawnUfo => {
+            game.ufo = Some(create_ufo(&game.assets, game.window.size, game.player_shots_fired));
+            String::from("ufo spawned")
+        },
+        ConsoleCommand::SpawnBoss => {
+            game.boss = Some(create_boss(&game.assets, game.window.size));
+            String::from("boss spawned")
+        },
+        ConsoleCommand::SpawnPowerup(powerup_type) => {
+            let position = game.player.position - Vec2::new(0.0, game.window.size.y / 3.0);
+            let powerup = match powerup_type {
+                Some(powerup_type) => create_powerup(powerup_type, position),
+                None => create_random_powerup(position)
+            };
+
+            game.powerups.push(powerup);
+            String::from("powerup spawned")
+        },
+        ConsoleCommand::KillRow(row) => {
+            let killed = kill_row(game, row);
+            game.clear_dead_actors();
+            game.enemies_controler.enemies_rect = get_enemies_rect(&game.enemies);
+            format!("{} enemies killed", killed)
+        },
+        ConsoleCommand::KillAll => {
+            let killed = game.enemies.len();
+
+            for enemie in &mut game.enemies {
+                enemie.kill();
+            }
+
+            for alive_enemies_row in &mut game.enemies_controler.enemies_alive_flags {
+                alive_enemies_row.fill(false);
+            }
+
+            game.clear_dead_actors();
+            game.enemies_controler.enemies_rect = get_enemies_rect(&game.enemies);
+            format!("{} enemies killed", killed)
+        },
+        ConsoleCommand::SetLives(lives) => {
+            let lives = lives.clamp(0.0, game.extra_life_awards.settings.max_lives);
+            game.player.hp = lives;
+            game.player.alive = lives > 0.0;
+            format!("lives set to {}", lives)
+        },
+        ConsoleCommand::SetTick(tick) => {
+            game.enemies_controler.tick_time = tick;
+            format!("tick set to {}", tick)
+        },
+        ConsoleCommand::God => {
+            game.god_mode = !game.god_mode;
+            format!("god mode {}", if game.god_mode { "on" } else { "off" })
+        },
+        ConsoleCommand::Wave(wave) => {
+            game.skip_to_wave(wave);
+            format!("wave {}", game.wave)
+        },
+        ConsoleCommand::ShieldReset => {
+            game.shields = create_shileds(&game.assets, &game.window);
+            game.hud.reset_shield_elements();
+            String::from("shields reset")
+        },
+        ConsoleCommand::Seed(seed) => {
+            seed_game_rng(seed);
+            format!("seed set to {}", seed)
+        }
+    }
+}
+
+pub fn run_console_line(game: &mut GameState, line: &str) -> String {
+    match parse_command(line) {
+        Ok(command) => {
+            game.replay.tainted = true;
+            execute_command(game, command)
+        },
+        Err(error) => error
+    }
+}
+
+//Runs CONSOLE_SCRIPT_FILE when it exists in the resources or in the user directory
+pub fn run_console_script(game: &mut GameState, ctx: &Context) {
+    if !ctx.fs.is_file(CONSOLE_SCRIPT_FILE) {
+        return;
+    }
+
+    let mut script = String::new();
+
+    if let Err(error) = ctx.fs.open(CONSOLE_SCRIPT_FILE).and_then(|mut file| file.read_to_string(&mut script).map_err(Into::into)) {
+        game.console.print(format!("can't read {}: {}", CONSOLE_SCRIPT_FILE, error));
+        return;
+    }
+
+    for line in script.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let message = run_console_line(game, line);
+        game.console.print(format!("> {}", line));
+        game.console.print(message);
+    }
+}
+
+pub fn draw_console(canvas: &mut graphics::Canvas, assets: &Assets, console: &Console, window_size: Vec2) {
+    let mut lines = console.output.clone();
+    lines.push(format!("> {}_", console.input));
+    let text = lines.join("\n");
+
+    let text_size = BitmapFont::text_dimensions(&text, CONSOLE_TEXT_SIZE);
+
+    canvas.draw(&assets.pixel_image, graphics::DrawParam::new()
+        .dest(Vec2::ZERO)
+        .scale(Vec2::new(window_size.x, text_size.y + CONSOLE_MARGIN * 2.0))
+        .color(Color::from_rgba(0, 0, 0, 200)));
+
+    assets.font.draw(canvas, &text, Vec2::splat(CONSOLE_MARGIN), CONSOLE_TEXT_SIZE, Color::GREEN);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let cases = [
+            ("spawn ufo", ConsoleCommand::SpawnUfo),
+            ("  SPAWN   Boss ", ConsoleCommand::SpawnBoss),
+            ("spawn powerup", ConsoleCommand::SpawnPowerup(None)),
+            ("spawn powerup triple_shot", ConsoleCommand::SpawnPowerup(Some(PowerupType::TripleShot))),
+            ("spawn powerup LASER", ConsoleCommand::SpawnPowerup(Some(PowerupType::PiercingLaser))),
+            ("kill all", ConsoleCommand::KillAll),
+            ("kill row 3", ConsoleCommand::KillRow(3)),
+            ("set lives 2.5", ConsoleCommand::SetLives(2.5)),
+            ("set tick 0.1", ConsoleCommand::SetTick(0.1)),
+            ("god", ConsoleCommand::God),
+            ("wave 12", ConsoleCommand::Wave(12)),
+            ("shield reset", ConsoleCommand::ShieldReset),
+            ("seed 18446744073709551615", ConsoleCommand::Seed(u64::MAX)),
+        ];
+
+        for (line, command) in cases {
+            assert_eq!(parse_command(line), Ok(command), "{}", line);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("", ""),
+            ("   ", ""),
+            ("fly", "unknown command fly"),
+            ("spawn dragon", "unknown command spawn dragon"),
+            ("god mode", "unknown command god mode"),
+            ("spawn powerup bomb", "unknown powerup bomb"),
+            ("kill row top", "usage: kill row <1-5>"),
+            ("kill row -1", "usage: kill row <1-5>"),
+            ("set lives many", "usage: set lives <n>"),
+            ("set lives inf", "usage: set lives <n>"),
+            ("set lives nan", "usage: set lives <n>"),
+            ("set tick 0", "tick must be above 0 and at most 10 seconds"),
+            ("set tick -1", "tick must be above 0 and at most 10 seconds"),
+            ("set tick nan", "tick must be above 0 and at most 10 seconds"),
+            ("set tick inf", "tick must be above 0 and at most 10 seconds"),
+            ("set tick 1e9", "tick must be above 0 and at most 10 seconds"),
+            ("wave 1.5", "usage: wave <n>"),
+            ("seed -3", "usage: seed <n>"),
+        ];
+
+        for (line, error) in cases {
+            assert_eq!(parse_command(line), Err(String::from(error)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn every_powerup_can_be_spawned_by_name() {
+        for powerup_type in POWERUP_TYPES {
+            let line = format!("spawn powerup {}", powerup_type.name().to_lowercase().replace(' ', "_"));
+            assert_eq!(parse_command(&line), Ok(ConsoleCommand::SpawnPowerup(Some(powerup_type))));
+        }
+    }
+}
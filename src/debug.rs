@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use ggez::glam::*;
+use ggez::graphics::{self, Color, Rect};
+use ggez::input::keyboard::KeyCode;
+
+use crate::actor::{world_to_screen_coords, Actor};
+use crate::assets::Assets;
+use crate::boss::boss_bounds;
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::rewind::{RewindBuffer, REWIND_BACK_KEY, REWIND_FORWARD_KEY};
+use crate::snapshot::{restore_snapshot, take_snapshot};
+
+pub const DEBUG_TEXT_SIZE : f32 = 10.0;
+pub const DEBUG_PANEL_TOP : f32 = 90.0;
+pub const TIME_SCALES : [f32; 4] = [1.0, 0.5, 0.25, 0.1];
+//Bullet positions remembered for the trails
+pub const MAX_TRAIL_POINTS : usize = 600;
+pub const TRAIL_POINT_SIZE : f32 = 2.0;
+
+pub const DEBUG_TOGGLE_KEY : KeyCode = KeyCode::F3;
+pub const DEBUG_PAUSE_KEY : KeyCode = KeyCode::F4;
+pub const DEBUG_STEP_KEY : KeyCode = KeyCode::F5;
+pub const DEBUG_TIME_SCALE_KEY : KeyCode = KeyCode::F6;
+
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub paused: bool,
+    //Single step asked while paused
+    pub step_requested: bool,
+    pub time_scale_index: usize,
+    pub trail: VecDeque<(Vec2, Color)>,
+    pub rewind: RewindBuffer,
+}
+
+impl DebugOverlay {
+    pub fn time_scale(&self) -> f32 {
+        TIME_SCALES[self.time_scale_index % TIME_SCALES.len()]
+    }
+
+    //Returns true when the key was used by the overlay
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if key == DEBUG_TOGGLE_KEY {
+            self.enabled = !self.enabled;
+
+            //Leaving the overlay gives back a running game at normal speed
+            if !self.enabled {
+                self.paused = false;
+                self.time_scale_index = 0;
+                self.trail.clear();
+                self.rewind.clear();
+            }
+
+            return true;
+        }
+
+        if !self.enabled {
+            return false;
+        }
+
+        match key {
+            DEBUG_PAUSE_KEY => {
+                self.paused = !self.paused;
+
+                if !self.paused {
+                    self.rewind.resume();
+                }
+            },
+            DEBUG_STEP_KEY => {
+                self.step_requested = self.paused;
+                self.rewind.resume();
+            },
+            DEBUG_TIME_SCALE_KEY => self.time_scale_index = (self.time_scale_index + 1) % TIME_SCALES.len(),
+            _ => return false
+        }
+
+        true
+    }
+
+    //Consumes the single step request
+    pub fn take_step(&mut self) -> bool {
+        std::mem::take(&mut self.step_requested)
+    }
+
+    pub fn record_trails(&mut self, player_bullets: &[Actor], enemy_bullets: &[Actor]) {
+        if !self.enabled {
+            return;
+        }
+
+        for bullet in player_bullets {
+            self.trail.push_back((bullet.position, Color::from_rgb(90, 200, 255)));
+        }
+
+        for bullet in enemy_bullets {
+            self.trail.push_back((bullet.position, Color::from_rgb(255, 120, 90)));
+        }
+
+        while self.trail.len() > MAX_TRAIL_POINTS {
+            self.trail.pop_front();
+        }
+    }
+}
+
+//Remembers the state at the start of the tick about to be simulated
+pub fn record_rewind(game: &mut GameState) {
+    if !game.debug.enabled {
+        return;
+    }
+
+    let snapshot = take_snapshot(game);
+    let simulation_rate = game.timestep.simulation_rate();
+    game.debug.rewind.record(snapshot, simulation_rate);
+}
+
+//Scrubs through the recorded ticks while paused, returns true when the key was used
+pub fn handle_rewind_key(game: &mut GameState, key: KeyCode) -> bool {
+    if !game.debug.enabled || !game.debug.paused {
+        return false;
+    }
+
+    let snapshot = match key {
+        REWIND_BACK_KEY => game.debug.rewind.step_back().cloned(),
+        REWIND_FORWARD_KEY => game.debug.rewind.step_forward().cloned(),
+        _ => return false
+    };
+
+    if let Some(snapshot) = snapshot {
+        restore_snapshot(game, snapshot);
+    }
+
+    true
+}
+
+//Rect in world coordinates
+fn draw_rect_outline(canvas: &mut graphics::Canvas, assets: &Assets, rect: Rect, window_size: Vec2, color: Color) {
+    let top_left = world_to_screen_coords(window_size.x, window_size.y, rect.point().into());
+    let sides = [
+        (top_left, Vec2::new(rect.w, 1.0)),
+        (top_left + Vec2::new(0.0, rect.h - 1.0), Vec2::new(rect.w, 1.0)),
+        (top_left, Vec2::new(1.0, rect.h)),
+        (top_left + Vec2::new(rect.w - 1.0, 0.0), Vec2::new(1.0, rect.h)),
+    ];
+
+    for (dest, scale) in sides {
+        canvas.draw(&assets.pixel_image, graphics::DrawParam::new().dest(dest).scale(scale).color(color));
+    }
+}
+
+fn debug_panel_text(game: &GameState, fps: f64) -> String {
+    let controler = &game.enemies_controler;
+
+    let mut lines = vec![
+        format!("FPS {:.0}  SIM {}HZ  SPEED X{}", fps, game.timestep.simulation_rate(), game.debug.time_scale()),
+        format!("PLAYER BULLETS {}  ENEMY BULLETS {}", game.player_bullets.len(), game.enemy_bullets.len()),
+        format!("ENEMIES {}  DIVERS {}  POWERUPS {}", game.enemies.len(), game.divers.len(), game.powerups.len()),
+        format!("TIME_TO_UPDATE {:.2}  TICK_TIME {:.2}", controler.time_to_update, controler.tick_time),
+        format!("TIME_TO_UPDATE_INNER {:.2}", controler.time_to_update_inner),
+        format!("TIME_TO_SHOOT {:.2}  FROM_LAST_SHOT {:.2}", controler.time_to_shoot, controler.time_from_last_shot),
+        format!("ROW_TO_MOVE {}  START_TO_MOVE {}", controler.row_to_move, controler.start_to_move),
+        format!("TIME_TO_DIVE {:.2}  FROM_LAST_DIVE {:.2}", controler.time_to_dive, controler.time_from_last_dive),
+        format!("WAVE {}  SHOT_TIMEOUT {:.2}", game.wave, game.player_shot_timeout),
+    ];
+
+    if game.debug.paused {
+        lines.push(String::from("PAUSED - F5 STEP  F7/F8 REWIND"));
+        lines.push(format!("REWIND -{} / {} TICKS", game.debug.rewind.ticks_back(), game.debug.rewind.snapshots.len()));
+    }
+
+    lines.join("\n")
+}
+
+//Hitboxes, formation bounds, the base line, bullet trails and the controller panel
+pub fn draw_debug_overlay(canvas: &mut graphics::Canvas, game: &GameState, fps: f64) {
+    let assets = &game.assets;
+    let window_size = game.window.size;
+
+    for (position, color) in &game.debug.trail {
+        let dest = world_to_screen_coords(window_size.x, window_size.y, *position) - Vec2::splat(TRAIL_POINT_SIZE / 2.0);
+        canvas.draw(&assets.pixel_image, graphics::DrawParam::new().dest(dest).scale(Vec2::splat(TRAIL_POINT_SIZE)).color(Color::new(color.r, color.g, color.b, 0.5)));
+    }
+
+    draw_rect_outline(canvas, assets, game.player.get_rect(), window_size, Color::GREEN);
+
+    for actor in game.enemies.iter().chain(game.divers.iter()).chain(game.ufo.iter()) {
+        draw_rect_outline(canvas, assets, actor.get_rect(), window_size, Color::RED);
+    }
+
+    for actor in game.player_bullets.iter().chain(game.enemy_bullets.iter()).chain(game.powerups.iter()) {
+        draw_rect_outline(canvas, assets, actor.get_rect(), window_size, Color::YELLOW);
+    }
+
+    for shield in game.shields.iter().filter(|shield| shield.is_alive()) {
+        draw_rect_outline(canvas, assets, shield.get_rect(), window_size, Color::CYAN);
+    }
+
+    if let Some(boss) = &game.boss {
+        for part in &boss.parts {
+            draw_rect_outline(canvas, assets, part.actor.get_rect(), window_size, Color::RED);
+        }
+
+        draw_rect_outline(canvas, assets, boss_bounds(boss), window_size, Color::MAGENTA);
+    }
+
+    if !game.enemies.is_empty() {
+        draw_rect_outline(canvas, assets, game.enemies_controler.enemies_rect, window_size, Color::MAGENTA);
+    }
+
+    //Game over line
+    let base_y = game.player.get_rect().top();
+    draw_rect_outline(canvas, assets, Rect::new(-window_size.x / 2.0, base_y, window_size.x, 1.0), window_size, Color::from_rgb(255, 80, 80));
+
+    let text = debug_panel_text(game, fps);
+    let panel_size = BitmapFont::text_dimensions(&text, DEBUG_TEXT_SIZE);
+    let panel_position = Vec2::new(8.0, DEBUG_PANEL_TOP);
+
+    canvas.draw(&assets.pixel_image, graphics::DrawParam::new()
+        .dest(panel_position - Vec2::splat(4.0))
+        .scale(panel_size + Vec2::splat(8.0))
+        .color(Color::from_rgba(0, 0, 0, 180)));
+
+    assets.font.draw(canvas, &text, panel_position, DEBUG_TEXT_SIZE, Color::WHITE);
+}
@@ -0,0 +1,73 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::{Context, GameResult};
+
+use crate::game::Window;
+use crate::ufo::UFO_TOP_OFFSET;
+
+pub const CRT_SHADER_FILE : &str = "/crt.wgsl";
+//Height of the red strip around the ufo row
+pub const CELLOPHANE_RED_HEIGHT : f32 = 60.0;
+//The green strip covers the shields, the player and the lives from here down
+pub const CELLOPHANE_GREEN_TOP : f32 = 0.72;
+pub const CELLOPHANE_RED : Color = Color::new(1.0, 0.25, 0.25, 1.0);
+pub const CELLOPHANE_GREEN : Color = Color::new(0.3, 1.0, 0.3, 1.0);
+
+//Coloured strips glued on the monochrome screen of the arcade cabinet, only the lit sprites show their colour
+pub fn draw_cellophane(canvas: &mut graphics::Canvas, window_size: Vec2) {
+    let green_top = window_size.y * CELLOPHANE_GREEN_TOP;
+    let bands = [
+        (graphics::Rect::new(0.0, UFO_TOP_OFFSET - CELLOPHANE_RED_HEIGHT / 2.0, window_size.x, CELLOPHANE_RED_HEIGHT), CELLOPHANE_RED),
+        (graphics::Rect::new(0.0, green_top, window_size.x, window_size.y - green_top), CELLOPHANE_GREEN),
+    ];
+
+    canvas.set_blend_mode(graphics::BlendMode::MULTIPLY);
+
+    for (rect, color) in bands {
+        canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(color));
+    }
+
+    canvas.set_blend_mode(graphics::BlendMode::ALPHA);
+}
+
+//Scanlines, curvature and bloom applied to the whole frame once it is drawn offscreen
+pub struct CrtEffect {
+    pub shader: graphics::Shader,
+}
+
+pub fn create_crt_effect(ctx: &Context) -> GameResult<CrtEffect> {
+    let shader = graphics::ShaderBuilder::new()
+        .fragment_path(CRT_SHADER_FILE)
+        .build(&ctx.gfx)?;
+
+    Ok(CrtEffect { shader })
+}
+
+//Sends the canvases of the window to an offscreen image the size of the window
+pub fn begin_offscreen(ctx: &Context, window: &mut Window) {
+    let (width, height) = ctx.gfx.drawable_size();
+    let (width, height) = (width as u32, height as u32);
+
+    let fits = match &window.target {
+        Some(target) => target.width() == width && target.height() == height,
+        None => false
+    };
+
+    if !fits {
+        window.target = Some(graphics::Image::new_canvas_image(ctx, ctx.gfx.surface_format(), width, height, 1));
+    }
+}
+
+//Draws the offscreen image to the frame through the shader
+pub fn present_crt(ctx: &mut Context, window: &Window, crt: &CrtEffect) -> GameResult {
+    let target = match &window.target {
+        Some(target) => target,
+        None => return Ok(())
+    };
+
+    let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+    canvas.set_sampler(graphics::Sampler::linear_clamp());
+    canvas.set_shader(&crt.shader);
+    canvas.draw(target, graphics::DrawParam::new());
+    canvas.finish(ctx)
+}
@@ -0,0 +1,477 @@
+use ggez::glam::*;
+use ggez::graphics::Rect;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::actor::{create_bullet, Actor, ActorType};
+use crate::assets::Assets;
+use crate::rng::game_rng;
+
+pub const ENEMY_LIFE : f32 = 1.0;
+pub const ENEMY_HORIZONTAL_SPACING : f32 = 20.0;
+pub const ENEMY_VERTICAL_SPACING : f32 = 20.0;
+pub const ENEMY_SCALE : f32 = 0.7;
+pub const ENEMY_START_TICK: f32 = 2.0;
+pub const ENEMY_START_SHOT_TIMER: f32 = 3.0;
+pub const ENEMY_JUMP : f32 = 10.0;
+pub const ENEMY_SPREAD : f32 = 0.35;
+pub const ENEMY_START_DIVE_TIMER : f32 = 6.0;
+pub const ENEMY_DIVE_SPEED : f32 = 220.0;
+pub const ENEMY_DIVE_SWAY : f32 = 120.0;
+pub const ENEMY_DIVE_SWAY_FREQUENCY : f32 = 0.03;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FirePattern {
+    Straight,
+    //Bullet flies toward the player position at the time of the shot
+    Aimed,
+    //Number of bullets fanned out downwards
+    Spread(u32),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct EnemyDefinition {
+    pub hp: f32,
+    pub score: u32,
+    pub bullet_type: ActorType,
+    pub fire_pattern: FirePattern,
+    pub dives: bool,
+}
+
+//Behaviour of every enemy type, the game keeps one copy so it can be changed at runtime
+#[derive(Debug, Copy, Clone)]
+pub struct EnemyDefinitions {
+    pub enemy_a: EnemyDefinition,
+    pub enemy_b: EnemyDefinition,
+    pub enemy_c: EnemyDefinition,
+}
+
+impl Default for EnemyDefinitions {
+    fn default() -> Self {
+        EnemyDefinitions {
+            enemy_a: EnemyDefinition {
+                hp: ENEMY_LIFE,
+                score: 50,
+                bullet_type: ActorType::EnemyBulletZigZag,
+                fire_pattern: FirePattern::Straight,
+                dives: false,
+            },
+            enemy_b: EnemyDefinition {
+                hp: ENEMY_LIFE * 2.0,
+                score: 100,
+                bullet_type: ActorType::EnemyBulletSlow,
+                fire_pattern: FirePattern::Aimed,
+                dives: false,
+            },
+            enemy_c: EnemyDefinition {
+                hp: ENEMY_LIFE,
+                score: 150,
+                bullet_type: ActorType::EnemyBulletFast,
+                fire_pattern: FirePattern::Spread(3),
+                dives: true,
+            },
+        }
+    }
+}
+
+impl EnemyDefinitions {
+    //Arcade enemies, one hit kills and 10/20/30 points from the bottom to the top rows
+    pub fn classic() -> Self {
+        let classic_enemy = EnemyDefinition {
+            hp: ENEMY_LIFE,
+            score: 10,
+            bullet_type: ActorType::EnemyBulletSlow,
+            fire_pattern: FirePattern::Straight,
+            dives: false,
+        };
+
+        EnemyDefinitions {
+            enemy_a: classic_enemy,
+            enemy_b: EnemyDefinition { score: 20, ..classic_enemy },
+            enemy_c: EnemyDefinition { score: 30, ..classic_enemy },
+        }
+    }
+
+    pub fn get(&self, tag: ActorType) -> Option<&EnemyDefinition> {
+        match tag {
+            ActorType::EnemyA => Some(&self.enemy_a),
+            ActorType::EnemyB => Some(&self.enemy_b),
+            ActorType::EnemyC => Some(&self.enemy_c),
+            _ => None
+        }
+    }
+
+    pub fn is_damaged(&self, enemy: &Actor) -> bool {
+        match self.get(enemy.tag) {
+            Some(definition) => enemy.hp < definition.hp,
+            None => false
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EnemyWallCollisionType {
+    Left,
+    Right,
+    None
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemiesControler{
+    pub enemies_rect: Rect,
+    pub time_to_update: f32,
+    pub time_to_update_inner: f32,
+    pub tick_time: f32,
+    pub time_to_shoot: f32,
+    pub time_from_last_shot: f32,
+    pub last_collision_type: EnemyWallCollisionType,
+    pub start_to_move: bool,
+    pub row_to_move: u32,
+    pub enemies_alive_flags: Vec<Vec<bool>>,
+    pub time_to_dive: f32,
+    pub time_from_last_dive: f32,
+    pub time_from_last_ufo: f32,
+    pub time_from_last_erosion: f32,
+    pub classic_shot_index: usize,
+    pub plunger_column_index: usize,
+    pub squiggly_column_index: usize,
+}
+
+pub fn create_enemy() -> Actor {
+    Actor{
+        tag: ActorType::EnemyA,
+        position: Vec2::ZERO,
+        direction: Vec2::ZERO,
+        size: Vec2::ZERO,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: ENEMY_LIFE,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+    }
+}
+
+pub fn create_enemies_controler() -> EnemiesControler {
+    EnemiesControler {
+        enemies_rect: Rect::zero(),
+        time_to_update: 0.0,
+        time_to_update_inner: 0.0,
+        tick_time: ENEMY_START_TICK,
+        time_to_shoot: ENEMY_START_SHOT_TIMER,
+        time_from_last_shot: 0.0,
+        last_collision_type: EnemyWallCollisionType::None,
+        start_to_move: false,
+        row_to_move: 5,
+        enemies_alive_flags: vec![vec![true; 11]; 5],
+        time_to_dive: ENEMY_START_DIVE_TIMER,
+        time_from_last_dive: 0.0,
+        time_from_last_ufo: 0.0,
+        time_from_last_erosion: 0.0,
+        classic_shot_index: 0,
+        plunger_column_index: 0,
+        squiggly_column_index: 0,
+     }
+}
+
+pub fn create_enemies(assets: &Assets, definitions: &EnemyDefinitions) -> Vec<Actor> {
+    let mut enemies : Vec<Actor> = Vec::new();
+    let enemy_size = Vec2{ x: assets.enemie_images[0].width() as f32 * ENEMY_SCALE, y: assets.enemie_images[0].height() as f32 * ENEMY_SCALE} ;
+
+    for i in 0..5 {
+        let next_position_vertical = (-5.0 * (enemy_size.y + ENEMY_VERTICAL_SPACING)) + (i as f32 * (enemy_size.y + ENEMY_VERTICAL_SPACING));
+        let enemy_tag = match i  {
+            0 => ActorType::EnemyC,
+            1..=2 => ActorType::EnemyB,
+            3..=4 => ActorType::EnemyA,
+            _ => panic!("No enemy type for this index {}", i)
+        };
+
+        for j in 0..11 {
+            let mut enemie = create_enemy();
+            let next_position_horizontal = (-5.0 * (enemy_size.x + ENEMY_HORIZONTAL_SPACING)) + (j as f32 * (enemy_size.x + ENEMY_HORIZONTAL_SPACING));
+
+            enemie.tag = enemy_tag;
+            enemie.position = Vec2{ x: next_position_horizontal, y: next_position_vertical};
+            enemie.size = Vec2{ x: enemy_size.x, y: enemy_size.y };
+            enemie.scale = Vec2{ x: ENEMY_SCALE, y: ENEMY_SCALE};
+            enemie.direction = Vec2{ x: 1.0, y: 0.0 };
+
+            if let Some(definition) = definitions.get(enemy_tag) {
+                enemie.hp = definition.hp;
+            }
+
+            enemies.push(enemie);
+        }
+    }
+
+    enemies
+}
+
+pub fn update_enemies_position(enemies_controler: &mut EnemiesControler, enemies: &mut [Actor], delta_time: f32) {
+    if enemies.is_empty() {
+        return;
+    }
+
+    enemies_controler.time_to_update += delta_time;
+
+    //Check if the enemy move sequence has to start
+    if enemies_controler.time_to_update > enemies_controler.tick_time {
+        enemies_controler.start_to_move = true;
+
+        //Start counting the inner move times
+        enemies_controler.time_to_update_inner += delta_time;
+    }
+
+    //Every one fifth of the tick_time move the next row of enemies
+    if enemies_controler.time_to_update_inner > enemies_controler.tick_time / 5.0 && enemies_controler.start_to_move {
+        let mut start_end_row_counter = vec![0];
+        let mut enemy_index_counter : usize = 0;
+
+        //Calculate start and index of every row of enemies
+        for alive_enemies_rows in &enemies_controler.enemies_alive_flags {
+            for alive_enemie in alive_enemies_rows {
+                if *alive_enemie {
+                    enemy_index_counter += 1;
+                }
+            }
+
+            start_end_row_counter.push(enemy_index_counter);
+        }
+
+        let current_row = enemies_controler.row_to_move as usize;
+
+        for (i, enemy) in enemies.iter_mut().skip(start_end_row_counter[current_row - 1]).enumerate() {
+            if i + start_end_row_counter[current_row - 1] >= start_end_row_counter[current_row] {
+                break;
+            }
+
+            enemy.position += enemy.direction * ENEMY_JUMP;
+        }
+
+        //Row that just moved
+        enemies_controler.row_to_move -= 1;
+        //Inner move timer reset
+        enemies_controler.time_to_update_inner = 0.0;
+
+        if enemies_controler.row_to_move == 0 {
+            enemies_controler.row_to_move = start_end_row_counter.len() as u32 - 1;
+            enemies_controler.start_to_move = false;
+            enemies_controler.time_to_update = 0.0;
+        }
+    }
+}
+
+pub fn enemies_check_collision_with_walls(enemies_controler: &mut EnemiesControler, enemies: &mut [Actor], window_size: Vec2)
+{
+    if enemies_controler.time_to_update == 0.0 && enemies_controler.time_to_update_inner == 0.0 {
+        let top_left = Vec2{ x: enemies_controler.enemies_rect.left(), y: enemies_controler.enemies_rect.top() };
+        let bottom_right = Vec2{ x: enemies_controler.enemies_rect.right(), y: enemies_controler.enemies_rect.bottom() };
+
+        if bottom_right.x > window_size.x / 2.0 {
+            let diff = bottom_right.x - (window_size.x / 2.0);
+
+            for enemy in enemies {
+                enemy.direction = Vec2{ x: -1.0, y: 0.0 };
+                enemy.position += enemy.direction * diff;
+                enemy.direction = Vec2{ x: 0.0, y: 1.0 };
+                enemies_controler.last_collision_type = EnemyWallCollisionType::Right;
+            }
+        }
+        else if top_left.x < -window_size.x / 2.0 {
+            let diff = -(window_size.x / 2.0) - top_left.x;
+
+            for enemy in enemies {
+                enemy.direction = Vec2{ x: 1.0, y: 0.0 };
+                enemy.position += enemy.direction * diff;
+                enemy.direction = Vec2{ x: 0.0, y: 1.0 };
+                enemies_controler.last_collision_type = EnemyWallCollisionType::Left;
+            }
+        }
+        else if enemies_controler.last_collision_type == EnemyWallCollisionType::Right {
+            enemies_controler.last_collision_type = EnemyWallCollisionType::None;
+
+            for enemy in enemies {
+                enemy.direction = Vec2{ x: -1.0, y: 0.0 };
+            }
+        }
+        else if enemies_controler.last_collision_type == EnemyWallCollisionType::Left {
+            enemies_controler.last_collision_type = EnemyWallCollisionType::None;
+
+            for enemy in enemies {
+                enemy.direction = Vec2{ x: 1.0, y: 0.0 };
+            }
+        }
+    }
+}
+
+//base_y is the game over line, the top of the player ship
+pub fn check_if_enemies_reached_base(enemies_controler: &EnemiesControler, base_y: f32) -> bool {
+    if enemies_controler.enemies_rect.bottom() > base_y {
+        return  true;
+    }
+
+    false
+}
+
+pub fn get_enemies_rect(enemies: &[Actor]) -> Rect
+{
+    if enemies.is_empty() {
+        return Rect::zero();
+    }
+
+    let mut enemies_rect = enemies[0].get_rect();
+
+    for enemy in enemies.iter().skip(1) {
+        enemies_rect = enemies_rect.combine_with(enemy.get_rect());
+    }
+
+    enemies_rect
+}
+
+//Sets the alive flag of the enemy at the given index of the enemies vector to false
+//Returns the row and the column the enemy had in the formation
+pub fn remove_enemy_from_formation(enemies_controler: &mut EnemiesControler, enemy_index: usize) -> Option<(usize, usize)> {
+    let mut index_of_enemy : usize = 0;
+
+    for (row, alive_enemies_rows) in enemies_controler.enemies_alive_flags.iter_mut().enumerate() {
+        for (column, alive_enemie) in alive_enemies_rows.iter_mut().enumerate() {
+            if *alive_enemie {
+                if index_of_enemy == enemy_index {
+                    *alive_enemie = false;
+                    return Some((row, column));
+                }
+
+                index_of_enemy += 1
+            }
+        }
+    }
+
+    None
+}
+
+pub fn formation_row_cleared(enemies_controler: &EnemiesControler, row: usize) -> bool {
+    enemies_controler.enemies_alive_flags.get(row).is_some_and(|alive_enemies_row| alive_enemies_row.iter().all(|alive| !alive))
+}
+
+pub fn formation_column_cleared(enemies_controler: &EnemiesControler, column: usize) -> bool {
+    enemies_controler.enemies_alive_flags.iter().all(|alive_enemies_row| !alive_enemies_row.get(column).copied().unwrap_or(false))
+}
+
+//Detaches a random diving enemy from the formation, returns true if an enemy left the formation
+pub fn enemies_dive(enemies_controler: &mut EnemiesControler, enemies: &mut Vec<Actor>, divers: &mut Vec<Actor>, definitions: &EnemyDefinitions, target: Vec2, delta_time: f32) -> bool {
+    enemies_controler.time_from_last_dive += delta_time;
+
+    if enemies_controler.start_to_move || enemies_controler.time_from_last_dive < enemies_controler.time_to_dive {
+        return false;
+    }
+
+    let mut rng = game_rng();
+
+    enemies_controler.time_from_last_dive = 0.0;
+    enemies_controler.time_to_dive = rng.gen_range(4.0..8.0);
+
+    let candidates: Vec<usize> = enemies.iter().enumerate()
+        .filter(|(_, enemy)| definitions.get(enemy.tag).is_some_and(|definition| definition.dives))
+        .map(|(i, _)| i)
+        .collect();
+
+    if candidates.is_empty() {
+        return false;
+    }
+
+    let enemy_index = candidates[rng.gen_range(0..candidates.len())];
+    remove_enemy_from_formation(enemies_controler, enemy_index);
+
+    let mut diver = enemies.remove(enemy_index);
+    diver.direction = (target - diver.position).normalize_or_zero();
+
+    if diver.direction.y <= 0.0 {
+        diver.direction = Vec2{ x: 0.0, y: 1.0 };
+    }
+
+    divers.push(diver);
+    true
+}
+
+pub fn update_diver_position(actor: &mut Actor, dt: f32) {
+    actor.position += actor.direction * (ENEMY_DIVE_SPEED * dt);
+    actor.position.x += (actor.position.y * ENEMY_DIVE_SWAY_FREQUENCY).cos() * ENEMY_DIVE_SWAY * dt;
+}
+
+//Index in the enemies vector of the bottom enemy of every column, -1 for empty columns
+pub fn last_enemies_in_columns(enemies_controler: &EnemiesControler) -> Vec<i16> {
+    let mut last_enemies_in_collumn_indexes: Vec<i16> = vec![-1;11];
+
+    let mut itr : usize = 0;
+    for alive_enemies_rows in enemies_controler.enemies_alive_flags.iter() {
+        for (column, alive_enemie) in alive_enemies_rows.iter().enumerate() {
+            if *alive_enemie {
+                last_enemies_in_collumn_indexes[column] = itr as i16;
+                itr += 1;
+            }
+        }
+    }
+
+    last_enemies_in_collumn_indexes
+}
+
+fn fire_directions(fire_pattern: FirePattern, position: Vec2, target: Vec2) -> Vec<Vec2> {
+    let down = Vec2{ x: 0.0, y: 1.0 };
+
+    match fire_pattern {
+        FirePattern::Straight => vec![down],
+        FirePattern::Aimed => {
+            let direction = (target - position).normalize_or_zero();
+
+            if direction.y <= 0.0 {
+                vec![down]
+            }
+            else {
+                vec![direction]
+            }
+        },
+        FirePattern::Spread(count) => {
+            let middle = (count.max(1) - 1) as f32 / 2.0;
+
+            (0..count.max(1))
+                .map(|i| Vec2{ x: (i as f32 - middle) * ENEMY_SPREAD, y: 1.0 }.normalize())
+                .collect()
+        }
+    }
+}
+
+pub fn enemies_shoot(enemies_controler: &mut EnemiesControler, enemies: &[Actor], enemy_bullets: &mut Vec<Actor>, definitions: &EnemyDefinitions, target: Vec2, delta_time: f32) {
+    enemies_controler.time_from_last_shot += delta_time;
+
+    if enemies.is_empty() {
+        return;
+    }
+
+    if !enemies_controler.start_to_move && enemies_controler.time_from_last_shot > enemies_controler.time_to_shoot {
+        let mut rng = game_rng();
+
+        enemies_controler.time_from_last_shot = 0.0;
+        enemies_controler.time_to_shoot = rng.gen_range(0.5..3.0);
+
+        let mut last_enemies_in_collumn_indexes = last_enemies_in_columns(enemies_controler);
+        last_enemies_in_collumn_indexes.retain(|&index| index >= 0);
+
+        let random_enemy = rng.gen_range(0..last_enemies_in_collumn_indexes.len());
+        let random_enemy = last_enemies_in_collumn_indexes[random_enemy];
+        let random_enemy = &enemies[random_enemy as usize];
+
+        let (bullet_type, fire_pattern) = match definitions.get(random_enemy.tag) {
+            Some(definition) => (definition.bullet_type, definition.fire_pattern),
+            None => (ActorType::EnemyBulletSlow, FirePattern::Straight)
+        };
+
+        let bullet_position = random_enemy.position + Vec2{x: 0.0, y: 35.0};
+
+        for direction in fire_directions(fire_pattern, bullet_position, target) {
+            let mut bullet = create_bullet(bullet_type);
+            bullet.position = bullet_position;
+            bullet.direction = direction;
+            enemy_bullets.push(bullet);
+        }
+    }
+}
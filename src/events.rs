@@ -0,0 +1,45 @@
+use ggez::glam::*;
+
+use crate::actor::ActorType;
+use crate::powerups::PowerupType;
+
+//Things that happened in the simulation, the hud, audio and the other systems react to them
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameEvent {
+    //One for every trigger pull, a spread shot fires several projectiles
+    PlayerShot{ projectiles: u32 },
+    //First hit of a player bullet
+    ShotHit,
+    PlayerHit{ hp: f32 },
+    //Hit that did not destroy the enemy, armoured enemies and boss parts
+    EnemyDamaged{ kind: ActorType, pos: Vec2 },
+    EnemyKilled{ kind: ActorType, pos: Vec2 },
+    //Hit on a boss part, the boss is not an ActorType
+    BossDamaged{ pos: Vec2 },
+    BossKilled{ pos: Vec2 },
+    ShieldDamaged{ index: usize, hp: f32 },
+    ShieldRepaired{ index: usize, hp: f32 },
+    PowerupCollected(PowerupType),
+    ExtraLife,
+    ScoreChanged{ score: u32 },
+    //shields_standing counts the shields left when the wave ends
+    WaveCleared{ wave: u32, shields_standing: usize },
+    //score is the one of the run that ended, max_score the high score including it
+    GameOver{ score: u32, max_score: u32 },
+}
+
+//Events emitted during a tick, drained once at the end of it
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    pub events: Vec<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::Context;
+
+pub const GLYPH_WIDTH : usize = 5;
+pub const GLYPH_HEIGHT : usize = 7;
+pub const GLYPH_SPACING : usize = 1;
+pub const LINE_SPACING : usize = 3;
+
+//Every glyph is 7 rows of 5 bits, the highest of the 5 bits is the left most pixel
+fn glyph_rows(character: char) -> Option<[u8; GLYPH_HEIGHT]> {
+    let rows = match character {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '*' => [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '?' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        '<' => [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+        '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '\'' => [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '`' => [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00],
+        _ => return None
+    };
+
+    Some(rows)
+}
+
+const FONT_CHARACTERS : &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ :.,-+=*/%!?<>()'_`";
+
+//Monospaced retro font, every glyph is a small white image tinted when drawn
+pub struct BitmapFont {
+    glyphs: HashMap<char, graphics::Image>,
+}
+
+impl BitmapFont {
+    pub fn new(ctx: &mut Context) -> BitmapFont {
+        let mut glyphs = HashMap::new();
+
+        for character in FONT_CHARACTERS.chars() {
+            let rows = match glyph_rows(character) {
+                Some(rows) => rows,
+                None => panic!("No glyph for font character: {:?}", character),
+            };
+
+            let mut pixels = vec![0_u8; GLYPH_WIDTH * GLYPH_HEIGHT * 4];
+
+            for (y, row) in rows.iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if row & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                        let pixel = (y * GLYPH_WIDTH + x) * 4;
+                        pixels[pixel..pixel + 4].copy_from_slice(&[255, 255, 255, 255]);
+                    }
+                }
+            }
+
+            let image = graphics::Image::from_pixels(ctx, &pixels, graphics::ImageFormat::Rgba8UnormSrgb, GLYPH_WIDTH as u32, GLYPH_HEIGHT as u32);
+            glyphs.insert(character, image);
+        }
+
+        BitmapFont { glyphs }
+    }
+
+    fn glyph(&self, character: char) -> Option<&graphics::Image> {
+        if character == ' ' {
+            return None;
+        }
+
+        self.glyphs.get(&character.to_ascii_uppercase()).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    //Size of the text in pixels, text_size is the height of a single line
+    pub fn text_dimensions(text: &str, text_size: f32) -> Vec2 {
+        let pixel = text_size / GLYPH_HEIGHT as f32;
+        let lines = text.lines().count().max(1);
+        let longest_line = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+
+        let width = if longest_line > 0 { (longest_line * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING) as f32 * pixel } else { 0.0 };
+        let height = (lines * (GLYPH_HEIGHT + LINE_SPACING) - LINE_SPACING) as f32 * pixel;
+
+        Vec2::new(width, height)
+    }
+
+    //Draws the text with its top left corner at dest (screen coordinates)
+    pub fn draw(&self, canvas: &mut graphics::Canvas, text: &str, dest: Vec2, text_size: f32, color: Color) {
+        let pixel = text_size / GLYPH_HEIGHT as f32;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line_y = dest.y + (line_index * (GLYPH_HEIGHT + LINE_SPACING)) as f32 * pixel;
+
+            for (column, character) in line.chars().enumerate() {
+                if let Some(glyph) = self.glyph(character) {
+                    let drawparams = graphics::DrawParam::new()
+                        .dest(Vec2::new(dest.x + (column * (GLYPH_WIDTH + GLYPH_SPACING)) as f32 * pixel, line_y))
+                        .scale(Vec2::new(pixel, pixel))
+                        .color(color);
+
+                    canvas.draw(glyph, drawparams);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1244 @@
+use std::path::PathBuf;
+
+use ggez::event;
+use ggez::glam::*;
+use ggez::graphics;
+use ggez::input::gamepad::gilrs::{Axis, Button};
+use ggez::input::gamepad::GamepadId;
+use ggez::input::keyboard::KeyCode;
+use ggez::timer;
+use ggez::{Context, GameResult};
+
+use crate::actor::*;
+use crate::assets::Assets;
+use crate::attract::{draw_title_screen, AttractMode};
+use crate::audio::play_event_sound;
+use crate::boss::*;
+use crate::bot::Autopilot;
+use crate::classic::{classic_enemies_shoot, classic_tick_time};
+use crate::collision::{bullet_hit_points, bullet_hits_rect};
+use crate::console::{draw_console, run_console_line, run_console_script, Console};
+use crate::debug::{draw_debug_overlay, handle_rewind_key, record_rewind, DebugOverlay};
+use crate::effects::{begin_offscreen, create_crt_effect, draw_cellophane, present_crt, CrtEffect};
+use crate::enemies::*;
+use crate::events::{EventQueue, GameEvent};
+use crate::font::BitmapFont;
+use crate::hud::{create_hud, draw_hud, format_score, hud_handle_event, Hud, HudTag};
+use crate::input::{gamepad_key, InputState};
+use crate::leaderboard::{create_offline_leaderboard_client, load_leaderboard_client, LeaderboardClient};
+use crate::lives::*;
+use crate::menu::{create_main_menu, MainMenu, MenuEntry};
+use crate::powerups::*;
+use crate::replay::{create_replay_recorder, save_demo_replay, save_replay_file, Replay, ReplayRecorder};
+use crate::rng::{reseed_game_rng, seed_game_rng};
+use crate::ruleset::{Difficulty, Ruleset};
+use crate::scoring::*;
+use crate::settings::{draw_color_filter, handle_settings_key, load_settings, Settings, SettingsMenu};
+use crate::shields::{create_shileds, damage_shield, erode_shields, repair_shield, SHIELD_EROSION_TIME};
+use crate::snapshot::{load_snapshot, restore_snapshot, save_snapshot, snapshot_exists, take_snapshot, take_view_snapshot};
+use crate::spectator::SpectatorServer;
+use crate::stats::*;
+use crate::timestep::*;
+use crate::ufo::*;
+use crate::weapon::*;
+
+pub const SAVE_SNAPSHOT_KEY : KeyCode = KeyCode::F9;
+
+//Size of the playfield, the window scales it to its own size
+pub const WORLD_SIZE : Vec2 = Vec2::new(650.0, 700.0);
+
+#[derive(Debug)]
+pub struct Window {
+    pub size : Vec2,
+    //Image the canvases draw to when the frame is post-processed, None draws to the frame directly
+    pub target : Option<graphics::Image>,
+}
+
+impl Window {
+    //Canvas drawing the playfield scaled to the window, centered with black bars around it
+    pub fn canvas(&self, ctx: &mut Context, clear: Option<graphics::Color>) -> graphics::Canvas {
+        let (width, height) = ctx.gfx.drawable_size();
+        let scale = (width / self.size.x).min(height / self.size.y);
+        let view = Vec2::new(width, height) / scale;
+
+        let mut canvas = match &self.target {
+            Some(target) => graphics::Canvas::from_image(ctx, target.clone(), clear),
+            None => graphics::Canvas::from_frame(ctx, clear)
+        };
+
+        canvas.set_screen_coordinates(graphics::Rect::new((self.size.x - view.x) / 2.0, (self.size.y - view.y) / 2.0, view.x, view.y));
+        canvas
+    }
+}
+
+pub struct GameState {
+    pub input: InputState,
+    pub ruleset: Ruleset,
+    pub difficulty: Difficulty,
+    pub assets: Assets,
+    pub player: Actor,
+    pub player_shot_timeout: f32,
+    pub weapon: Weapon,
+    pub player_shots_fired: u32,
+    pub player_bullets: Vec<Actor>,
+    pub enemy_bullets: Vec<Actor>,
+    pub enemies: Vec<Actor>,
+    pub divers: Vec<Actor>,
+    pub enemy_definitions: EnemyDefinitions,
+    pub boss: Option<Boss>,
+    pub ufo: Option<Actor>,
+    //Actors destroyed during the current tick, consumed by handle_deaths
+    pub deaths: Vec<ActorDeath>,
+    pub events: EventQueue,
+    //Copy of the dispatched events for systems outside the game, like the versus match, None when nobody reads it
+    pub event_outbox: Option<Vec<GameEvent>>,
+    pub shields: Vec<Actor>,
+    pub powerups: Vec<Actor>,
+    pub active_powerups: ActivePowerups,
+    pub enemies_controler: EnemiesControler,
+    pub extra_life_awards: ExtraLifeAwards,
+    pub hud: Hud,
+    pub stats: StatsTracker,
+    pub scoring: Scoring,
+    pub timestep: FixedTimestep,
+    pub debug: DebugOverlay,
+    pub console: Console,
+    pub menu: MainMenu,
+    //Screens and demo game playing while the title screen is idle
+    pub attract: AttractMode,
+    pub settings: Settings,
+    pub settings_menu: SettingsMenu,
+    //Built the first time the CRT setting is on
+    pub crt: Option<CrtEffect>,
+    pub replay: ReplayRecorder,
+    //Replay of the run that just ended, submitted to the leaderboard
+    pub finished_replay: Option<Replay>,
+    pub leaderboard: LeaderboardClient,
+    //Bot or replay playing instead of the keyboard, its runs are not submitted
+    pub autopilot: Option<Autopilot>,
+    //File the replay of every finished run is written to
+    pub record_path: Option<PathBuf>,
+    //Replaying for a check, no sound is played and nothing is saved
+    pub headless: bool,
+    pub spectators: Option<SpectatorServer>,
+    //Cheat, the player can't be damaged
+    pub god_mode: bool,
+    pub show_stats: bool,
+    pub score: u32,
+    pub max_score: u32,
+    pub wave: u32,
+    pub window: Window,
+}
+
+impl GameState {
+    //The game played from the window, opens on the title screen
+    pub fn new(ctx: &mut Context) -> GameResult<GameState> {
+        let mut game = GameState::new_headless(ctx)?;
+        game.headless = false;
+        game.set_max_score(load_high_score(ctx));
+        game.stats.stats = load_stats(ctx);
+        game.leaderboard = load_leaderboard_client(ctx);
+        game.settings = load_settings(&ctx.fs);
+        game.apply_settings();
+        game.menu = create_main_menu(snapshot_exists(ctx));
+
+        run_console_script(&mut game, ctx);
+
+        Ok(game)
+    }
+
+    //Game without sound, saves, title screen or console script, for the replays, the demo and the other views of a game.
+    //Nothing is read from disk, the high score, the stats and the leaderboard start empty
+    pub fn new_headless(ctx: &mut Context) -> GameResult<GameState> {
+        let window = Window {
+            size : WORLD_SIZE,
+            target : None,
+        };
+
+        let assets = Assets::new(ctx);
+        let mut player = create_player();
+
+        player.position.y = (window.size.y / 2.0) - (window.size.y / 8.0);
+        player.size = Vec2{ x: assets.player_image.width() as f32, y: assets.player_image.height() as f32 };
+        let ruleset = Ruleset::default();
+        let enemy_definitions = ruleset.enemy_definitions();
+        let enemies = create_enemies(&assets, &enemy_definitions);
+        let shields = create_shileds(&assets, &window);
+
+        let mut hud = create_hud();
+        hud.set_text(HudTag::MaxScore, format_score(0));
+
+        let mut game = GameState {
+            input: InputState::default(),
+            ruleset,
+            difficulty: Difficulty::default(),
+            assets,
+            player,
+            player_shot_timeout: 0.0,
+            weapon: ruleset.weapon(),
+            player_shots_fired: 0,
+            player_bullets: Vec::new(),
+            enemy_bullets: Vec::new(),
+            enemies,
+            divers: Vec::new(),
+            enemy_definitions,
+            boss: None,
+            ufo: None,
+            deaths: Vec::new(),
+            events: EventQueue::default(),
+            event_outbox: None,
+            shields,
+            powerups: Vec::new(),
+            active_powerups: ActivePowerups::default(),
+            enemies_controler: create_enemies_controler(),
+            extra_life_awards: create_extra_life_awards(ExtraLifeSettings::default()),
+            hud,
+            stats: StatsTracker::default(),
+            show_stats: false,
+            scoring: create_scoring(ScoringMode::default()),
+            timestep: create_fixed_timestep(DEFAULT_SIMULATION_RATE),
+            debug: DebugOverlay::default(),
+            console: Console::default(),
+            menu: create_main_menu(false),
+            attract: AttractMode::default(),
+            settings: Settings::default(),
+            settings_menu: SettingsMenu::default(),
+            crt: None,
+            replay: ReplayRecorder::default(),
+            finished_replay: None,
+            leaderboard: create_offline_leaderboard_client(),
+            autopilot: None,
+            record_path: None,
+            headless: true,
+            spectators: None,
+            god_mode: false,
+            score: 0,
+            max_score: 0,
+            wave: 1,
+            window
+        };
+
+        game.menu.open = false;
+        game.start_replay();
+
+        Ok(game)
+    }
+
+
+    pub fn set_max_score(&mut self, max_score: u32) {
+        self.max_score = max_score;
+        self.hud.set_text(HudTag::MaxScore, format_score(max_score));
+    }
+
+    //Switches the rules and starts a new game
+    pub fn set_ruleset(&mut self, ruleset: Ruleset) {
+        self.ruleset = ruleset;
+        self.weapon = ruleset.weapon();
+        self.enemy_definitions = ruleset.enemy_definitions();
+        self.reset_game(true);
+    }
+
+    //Steps simulated every second, the rendering keeps the monitor rate
+    pub fn set_simulation_rate(&mut self, simulation_rate: u32) {
+        self.timestep.set_rate(simulation_rate);
+        self.replay_settings_changed();
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.replay_settings_changed();
+    }
+
+    //Applies the settings the simulation and the sound use, the window ones are applied by the settings menu
+    pub fn apply_settings(&mut self) {
+        self.assets.set_sound_volume(self.settings.sound_volume());
+        self.hud.set_visible(HudTag::Fps, self.settings.show_fps);
+
+        if self.ruleset != self.settings.ruleset {
+            self.set_ruleset(self.settings.ruleset);
+
+            //Nothing was played yet, the reset is not a game over
+            self.events.events.clear();
+            self.finished_replay = None;
+        }
+
+        if self.difficulty != self.settings.difficulty {
+            self.set_difficulty(self.settings.difficulty);
+        }
+    }
+
+    pub fn set_scoring_mode(&mut self, mode: ScoringMode) {
+        self.scoring = create_scoring(mode);
+        self.replay_settings_changed();
+    }
+
+    //Starts recording a new run from a fresh seed
+    pub fn start_replay(&mut self) {
+        let seed = reseed_game_rng();
+        self.replay = create_replay_recorder(self.ruleset, self.difficulty, self.scoring.mode, self.timestep.simulation_rate(), self.window.size, seed);
+    }
+
+    pub fn start_replay_with_seed(&mut self, seed: u64) {
+        seed_game_rng(seed);
+        self.replay = create_replay_recorder(self.ruleset, self.difficulty, self.scoring.mode, self.timestep.simulation_rate(), self.window.size, seed);
+    }
+
+    //The replays always start at the first wave, a skipped run can't be replayed
+    pub fn skip_to_wave(&mut self, wave: u32) {
+        self.replay.tainted = true;
+        self.wave = wave.max(1) - 1;
+        self.reset_game(false);
+    }
+
+    //Settings picked before the first step are part of the replay, later changes can't be replayed
+    fn replay_settings_changed(&mut self) {
+        if self.replay.is_empty() {
+            self.start_replay();
+        }
+        else {
+            self.replay.tainted = true;
+        }
+    }
+
+    pub fn current_weapon(&self) -> Weapon {
+        self.active_powerups.modify_weapon(&self.weapon)
+    }
+
+    pub fn fire_player_shot(&mut self) {
+        let weapon = self.current_weapon();
+
+        if !weapon.can_fire(self.player_bullets.len()) {
+            return;
+        }
+
+        self.player_shot_timeout = weapon.fire_time;
+        self.player_shots_fired += 1;
+
+        let bullet_image = self.assets.actor_image(&create_bullet(weapon.projectile));
+        let bullet_size = Vec2{ x: bullet_image.width() as f32, y: bullet_image.height() as f32 };
+
+        let bullets = weapon.fire(self.player.position + Vec2{x: 0.0, y: -10.0}, bullet_size);
+
+        for _ in &bullets {
+            self.scoring.register_shot();
+        }
+
+        self.events.push(GameEvent::PlayerShot{ projectiles: bullets.len() as u32 });
+
+        self.player_bullets.extend(bullets);
+    }
+
+    pub fn clear_dead_actors(&mut self) {
+        self.player_bullets.retain(|bullet| bullet.is_alive());
+        self.enemy_bullets.retain(|bullet| bullet.is_alive());
+        self.enemies.retain(|enemie| enemie.is_alive());
+        self.divers.retain(|diver| diver.is_alive());
+        self.powerups.retain(|powerup| powerup.is_alive());
+        self.ufo.take_if(|ufo| !ufo.is_alive());
+    }
+
+    pub fn collect_powerup(&mut self, powerup_type: PowerupType) {
+        self.events.push(GameEvent::PowerupCollected(powerup_type));
+
+        if powerup_type == PowerupType::ExtraLife {
+            if add_life(&mut self.player, self.extra_life_awards.settings.max_lives) {
+                self.events.push(GameEvent::ExtraLife);
+            }
+        }
+        else {
+            self.active_powerups.activate(powerup_type);
+        }
+    }
+
+    pub fn damage_player(&mut self, damage: f32) {
+        if self.god_mode {
+            return;
+        }
+
+        let death = self.player.damage(damage);
+        self.deaths.extend(death);
+        self.events.push(GameEvent::PlayerHit{ hp: self.player.hp.max(0.0) });
+    }
+
+    pub fn add_score(&mut self, points: u32) {
+        self.score += points;
+        self.events.push(GameEvent::ScoreChanged{ score: self.score });
+    }
+
+    pub fn handle_powerups(&mut self, delta_time: f32) {
+        let player_rect = self.player.get_rect();
+        let mut collected: Vec<PowerupType> = Vec::new();
+
+        for powerup in &mut self.powerups {
+            update_powerup_position(powerup, delta_time);
+            handle_out_off_screen(powerup, self.window.size);
+
+            if powerup.is_alive() && powerup.get_rect().overlaps(&player_rect) {
+                powerup.kill();
+
+                if let ActorType::Powerup(powerup_type) = powerup.tag {
+                    collected.push(powerup_type);
+                }
+            }
+        }
+
+        for powerup_type in collected {
+            self.collect_powerup(powerup_type);
+        }
+
+        if self.active_powerups.update(delta_time) {
+            for (i, shield) in self.shields.iter_mut().enumerate() {
+                repair_shield(i, shield, &mut self.events);
+            }
+        }
+
+        self.hud.set_text(HudTag::Powerups, self.active_powerups.timers_text());
+    }
+
+    pub fn handle_divers(&mut self, delta_time: f32) {
+        let player_rect = self.player.get_rect();
+        let mut rammed = 0;
+
+        for diver in &mut self.divers {
+            update_diver_position(diver, delta_time);
+            handle_out_off_screen(diver, self.window.size);
+
+            if diver.is_alive() && diver.get_rect().overlaps(&player_rect) {
+                diver.kill();
+                rammed += 1;
+            }
+        }
+
+        for _ in 0..rammed {
+            self.damage_player(self.ruleset.collision_rules().player_damage);
+        }
+    }
+
+    //Invaders low enough to reach the shields eat them away and kill the player on contact
+    pub fn handle_invasion(&mut self, delta_time: f32) {
+        self.enemies_controler.time_from_last_erosion += delta_time;
+
+        if self.enemies_controler.time_from_last_erosion > SHIELD_EROSION_TIME {
+            self.enemies_controler.time_from_last_erosion = 0.0;
+            let deaths = erode_shields(&mut self.shields, &self.enemies, &mut self.events);
+            self.deaths.extend(deaths);
+        }
+
+        let player_rect = self.player.get_rect();
+
+        if self.enemies.iter().any(|enemie| enemie.is_alive() && enemie.get_rect().overlaps(&player_rect)) {
+            self.damage_player(self.player.hp);
+        }
+    }
+
+    pub fn handle_ufo(&mut self, delta_time: f32) {
+        if let Some(ufo) = &mut self.ufo {
+            update_ufo_position(ufo, delta_time);
+            handle_out_off_screen(ufo, self.window.size);
+            self.ufo.take_if(|ufo| !ufo.is_alive());
+            return;
+        }
+
+        if !self.ruleset.ufo_enabled() {
+            return;
+        }
+
+        self.enemies_controler.time_from_last_ufo += delta_time;
+
+        if self.enemies_controler.time_from_last_ufo > UFO_SPAWN_TIME && self.enemies.len() >= UFO_MIN_ENEMIES {
+            self.enemies_controler.time_from_last_ufo = 0.0;
+            self.ufo = Some(create_ufo(&self.assets, self.window.size, self.player_shots_fired));
+        }
+    }
+
+    pub fn reset_game(&mut self, game_over: bool){
+        self.player_bullets.clear();
+        self.enemy_bullets.clear();
+        self.enemies.clear();
+        self.divers.clear();
+        self.powerups.clear();
+        self.deaths.clear();
+
+        let mut player = create_player();
+        player.position.y = (self.window.size.y / 2.0) - (self.window.size.y / 8.0);
+        player.size = Vec2{ x: self.assets.player_image.width() as f32, y: self.assets.player_image.height() as f32 };
+
+        if !game_over {
+            player.hp = self.player.hp;
+        }
+
+        let enemies = create_enemies(&self.assets, &self.enemy_definitions);
+
+        self.player = player;
+        self.enemies = enemies;
+        self.player_shot_timeout = 0.0;
+        self.enemies_controler = create_enemies_controler();
+        self.boss = None;
+        self.ufo = None;
+        self.wave = if game_over { 1 } else { self.wave + 1 };
+
+        if game_over {
+            self.player_shots_fired = 0;
+        }
+
+        if self.ruleset.bosses_enabled() && is_boss_wave(self.wave) {
+            self.enemies.clear();
+            self.enemies_controler.enemies_alive_flags = vec![vec![false; 11]; 5];
+            self.boss = Some(create_boss(&self.assets, self.window.size));
+        }
+
+        if game_over {
+            self.shields = create_shileds(&self.assets, &self.window);
+
+            self.active_powerups = ActivePowerups::default();
+            self.scoring.reset();
+
+            self.max_score = self.max_score.max(self.score);
+            self.finished_replay = self.replay.finish(self.score);
+            self.events.push(GameEvent::GameOver{ score: self.score, max_score: self.max_score });
+            self.score = 0;
+
+            self.events.push(GameEvent::ScoreChanged{ score: self.score });
+            self.extra_life_awards.reset();
+            self.start_replay();
+        }
+    }
+
+    pub fn award_extra_lives(&mut self) {
+        let mut life_added = false;
+
+        for _ in 0..self.extra_life_awards.check(self.score) {
+            life_added |= add_life(&mut self.player, self.extra_life_awards.settings.max_lives);
+        }
+
+        if life_added {
+            self.events.push(GameEvent::ExtraLife);
+        }
+    }
+
+    pub fn handle_collision(&mut self) {
+        let mut update_enemies_rect = false;
+        let mut formation_bonus: Option<(u32, Vec2)> = None;
+        let weapon = self.current_weapon();
+        let rules = self.ruleset.collision_rules();
+
+        'outer: for player_bullet in &mut self.player_bullets {
+            if !player_bullet.is_alive() {
+                continue;
+            }
+
+            let bullet_points = bullet_hit_points(&player_bullet.get_rect());
+
+            //Enemies
+            for (i, enemie) in &mut self.enemies.iter_mut().enumerate()  {
+                if !enemie.is_alive() {
+                    continue;
+                }
+                let enemie_rect =  enemie.get_rect();
+
+                let hit = bullet_hits_rect(&bullet_points, &enemie_rect);
+
+                if hit {
+                    if !weapon.piercing {
+                        player_bullet.kill();
+                    }
+
+                    let first_hit = player_bullet.mark_hit();
+                    self.scoring.register_hit(first_hit);
+
+                    if first_hit {
+                        self.events.push(GameEvent::ShotHit);
+                    }
+
+                    //Armoured enemies survive the hit
+                    match enemie.damage(weapon.damage) {
+                        Some(death) => self.deaths.push(death),
+                        None => {
+                            self.events.push(GameEvent::EnemyDamaged{ kind: enemie.tag, pos: enemie.position });
+                            continue 'outer;
+                        }
+                    }
+
+                    update_enemies_rect = true;
+
+                    //Updates the enemies_alive_flags setting false at position where the enemie was destroied
+                    if let Some((row, column)) = remove_enemy_from_formation(&mut self.enemies_controler, i) {
+                        let mut bonus = 0;
+
+                        if formation_row_cleared(&self.enemies_controler, row) {
+                            bonus += ROW_CLEAR_BONUS;
+                        }
+
+                        if formation_column_cleared(&self.enemies_controler, column) {
+                            bonus += COLUMN_CLEAR_BONUS;
+                        }
+
+                        formation_bonus = Some((bonus, enemie.position));
+                    }
+                    break 'outer;
+                }
+            }
+
+            //Diving enemies
+            for diver in &mut self.divers {
+                if !diver.is_alive() || !bullet_hits_rect(&bullet_points, &diver.get_rect()) {
+                    continue;
+                }
+
+                if !weapon.piercing {
+                    player_bullet.kill();
+                }
+
+                let first_hit = player_bullet.mark_hit();
+                self.scoring.register_hit(first_hit);
+
+                if first_hit {
+                    self.events.push(GameEvent::ShotHit);
+                }
+
+                match diver.damage(weapon.damage) {
+                    Some(death) => self.deaths.push(death),
+                    None => self.events.push(GameEvent::EnemyDamaged{ kind: diver.tag, pos: diver.position })
+                }
+                break;
+            }
+
+            //Ufo
+            if let Some(ufo) = &mut self.ufo {
+                if player_bullet.is_alive() && ufo.is_alive() && bullet_hits_rect(&bullet_points, &ufo.get_rect()) {
+                    player_bullet.kill();
+                    let first_hit = player_bullet.mark_hit();
+                    self.scoring.register_hit(first_hit);
+
+                    if first_hit {
+                        self.events.push(GameEvent::ShotHit);
+                    }
+
+                    self.deaths.extend(ufo.damage(weapon.damage));
+                }
+            }
+
+            //Boss
+            if let Some(boss) = &mut self.boss {
+                if player_bullet.is_alive() && damage_boss(boss, &bullet_points, weapon.damage) {
+                    player_bullet.kill();
+                    let first_hit = player_bullet.mark_hit();
+                    self.scoring.register_hit(first_hit);
+
+                    if first_hit {
+                        self.events.push(GameEvent::ShotHit);
+                    }
+
+                    self.events.push(GameEvent::BossDamaged{ pos: player_bullet.position });
+                }
+            }
+
+            if !player_bullet.is_alive() {
+                continue;
+            }
+
+            //Shields
+            for (i, shield) in self.shields.iter_mut().enumerate() {
+                if shield.is_alive() && bullet_hits_rect(&bullet_points, &shield.get_rect()) {
+                    self.deaths.extend(damage_shield(i, shield, player_bullet, rules.shield_damage, &mut self.events));
+
+                    //A piercing bullet that already hit an enemy is not a miss
+                    if !player_bullet.has_hit {
+                        self.scoring.register_miss();
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        if update_enemies_rect {
+            self.enemies_controler.enemies_rect = get_enemies_rect(&self.enemies);
+
+            self.enemies_controler.tick_time = match self.ruleset {
+                Ruleset::Modern => self.enemies_controler.tick_time * 0.8,
+                Ruleset::Classic => classic_tick_time(self.enemies.iter().filter(|enemie| enemie.is_alive()).count()),
+            };
+        }
+
+        if let Some((bonus, position)) = formation_bonus {
+            let points = self.scoring.bonus(bonus, position, "CLEAR");
+
+            if points > 0 {
+                self.add_score(points);
+            }
+        }
+
+        if let Some(boss) = self.boss.take_if(|boss| boss.hp <= 0.0) {
+            self.events.push(GameEvent::BossKilled{ pos: boss.position });
+            let points = self.scoring.award(BOSS_SCORE, boss.position);
+            self.add_score(points);
+            self.powerups.push(create_random_powerup(boss.position));
+        }
+
+        let player_rect = self.player.get_rect();
+        let mut player_hits = 0;
+
+        for enemy_bullet in &mut self.enemy_bullets {
+            if !enemy_bullet.is_alive() {
+                continue;
+            }
+
+            let bullet_points = bullet_hit_points(&enemy_bullet.get_rect());
+
+            //Hit player
+            if bullet_hits_rect(&bullet_points, &player_rect) {
+                enemy_bullet.kill();
+                player_hits += 1;
+                continue;
+            }
+
+            //Hit player bullet
+            for player_bullet in &mut self.player_bullets {
+                if !player_bullet.is_alive() || !bullet_hits_rect(&bullet_points, &player_bullet.get_rect()) {
+                    continue;
+                }
+
+                player_bullet.damage(rules.clash_damage);
+
+                if !rules.survives_clash(enemy_bullet.tag) && enemy_bullet.damage(rules.clash_damage).is_some() {
+                    break;
+                }
+            }
+
+            if !enemy_bullet.is_alive() {
+                continue;
+            }
+
+            //Shields
+            for (i, shield) in self.shields.iter_mut().enumerate() {
+                if shield.is_alive() && bullet_hits_rect(&bullet_points, &shield.get_rect()) {
+                    self.deaths.extend(damage_shield(i, shield, enemy_bullet, rules.shield_damage, &mut self.events));
+                    break;
+                }
+            }
+        }
+
+        for _ in 0..player_hits {
+            self.damage_player(rules.player_damage);
+        }
+
+        self.handle_deaths();
+        self.award_extra_lives();
+    }
+
+    //Awards the score and the powerup drops of the actors destroyed this tick
+    pub fn handle_deaths(&mut self) {
+        for death in std::mem::take(&mut self.deaths) {
+            match death.tag {
+                ActorType::EnemyA | ActorType::EnemyB | ActorType::EnemyC => {
+                    self.events.push(GameEvent::EnemyKilled{ kind: death.tag, pos: death.position });
+                    let points = self.scoring.award(self.enemy_definitions.get(death.tag).map_or(0, |definition| definition.score), death.position);
+                    self.add_score(points);
+
+                    if self.ruleset.powerups_enabled() {
+                        if let Some(powerup) = roll_powerup_drop(death.position) {
+                            self.powerups.push(powerup);
+                        }
+                    }
+                },
+                ActorType::Ufo => {
+                    self.events.push(GameEvent::EnemyKilled{ kind: death.tag, pos: death.position });
+                    let points = self.scoring.award(ufo_score(self.player_shots_fired), death.position);
+                    self.add_score(points);
+                },
+                _ => ()
+            }
+        }
+    }
+
+    //Remembers where every actor was before the step so the rendering can interpolate
+    pub fn store_previous_positions(&mut self) {
+        self.player.store_previous_position();
+
+        for actor in self.player_bullets.iter_mut()
+            .chain(self.enemy_bullets.iter_mut())
+            .chain(self.enemies.iter_mut())
+            .chain(self.divers.iter_mut())
+            .chain(self.powerups.iter_mut())
+            .chain(self.ufo.iter_mut()) {
+            actor.store_previous_position();
+        }
+
+        if let Some(boss) = &mut self.boss {
+            for part in &mut boss.parts {
+                part.actor.store_previous_position();
+            }
+        }
+    }
+
+    //Advances the simulation by one fixed step of delta_time seconds
+    pub fn step(&mut self, ctx: &Context, delta_time: f32) -> GameResult {
+        //The game is paused while the menu, the stats screen or the console are open
+        if self.menu.open || self.show_stats || self.console.open {
+            return Ok(());
+        }
+
+        if self.debug.enabled || self.god_mode {
+            self.replay.tainted = true;
+        }
+
+        if let Some(mut autopilot) = self.autopilot.take() {
+            if let Some(input) = autopilot.next_input(self) {
+                self.input = input;
+                self.autopilot = Some(autopilot);
+            }
+        }
+
+        self.replay.record(&self.input);
+        record_rewind(self);
+        self.store_previous_positions();
+        self.stats.stats.play_time += delta_time;
+
+        player_handle_input(&mut self.player, &self.input, delta_time);
+        player_check_collision_with_walls(&mut self.player, self.window.size);
+
+        self.player_shot_timeout -= delta_time;
+        if self.input.fire && self.player_shot_timeout < 0.0 {
+            self.fire_player_shot();
+        }
+
+        for act in &mut self.player_bullets {
+            update_bullet_position(act, delta_time);
+            handle_out_off_screen(act, self.window.size);
+
+            if !act.is_alive() && !act.has_hit {
+                self.scoring.register_miss();
+            }
+        }
+
+        for act in &mut self.enemy_bullets {
+            update_bullet_position(act, delta_time);
+            handle_out_off_screen(act, self.window.size);
+        }
+
+        update_enemies_position(&mut self.enemies_controler, &mut self.enemies, delta_time);
+        if self.enemies_controler.time_to_update == 0.0 {
+            self.enemies_controler.enemies_rect = get_enemies_rect(&self.enemies);
+        }
+
+        enemies_check_collision_with_walls(&mut self.enemies_controler, &mut self.enemies, self.window.size);
+        self.handle_invasion(delta_time);
+
+        let fire_delta_time = delta_time * self.difficulty.enemy_fire_rate();
+
+        match self.ruleset {
+            Ruleset::Modern => enemies_shoot(&mut self.enemies_controler, &self.enemies, &mut self.enemy_bullets, &self.enemy_definitions, self.player.position, fire_delta_time),
+            Ruleset::Classic => classic_enemies_shoot(&mut self.enemies_controler, &self.enemies, &mut self.enemy_bullets, self.player.position.x, self.ufo.is_some(), fire_delta_time),
+        }
+
+        self.handle_ufo(delta_time);
+
+        if enemies_dive(&mut self.enemies_controler, &mut self.enemies, &mut self.divers, &self.enemy_definitions, self.player.position, delta_time) {
+            self.enemies_controler.enemies_rect = get_enemies_rect(&self.enemies);
+        }
+
+        let laser_hit = match &mut self.boss {
+            Some(boss) => update_boss(boss, &self.assets, &self.enemy_definitions, &mut self.enemy_bullets, &mut self.divers, &self.player, self.window.size, delta_time),
+            None => false
+        };
+
+        if laser_hit {
+            self.damage_player(self.ruleset.collision_rules().player_damage);
+        }
+
+        self.handle_divers(delta_time);
+
+        self.handle_powerups(delta_time);
+
+        self.scoring.update(delta_time);
+        self.hud.set_text(HudTag::Combo, self.scoring.combo_text());
+
+        self.handle_collision();
+        self.clear_dead_actors();
+
+        if !self.player.is_alive() || check_if_enemies_reached_base(&self.enemies_controler, self.player.get_rect().top()) {
+            self.reset_game(true);
+        }
+
+        if self.enemies.is_empty() && self.divers.is_empty() && self.boss.is_none() {
+            let bonus = self.scoring.wave_bonus(self.player.position);
+
+            if bonus > 0 {
+                self.add_score(bonus);
+            }
+
+            let shields_standing = self.shields.iter().filter(|shield| shield.is_alive()).count();
+            self.events.push(GameEvent::WaveCleared{ wave: self.wave, shields_standing });
+            self.reset_game(false);
+        }
+
+        self.dispatch_events(ctx)?;
+        self.publish_to_spectators();
+
+        Ok(())
+    }
+
+    pub fn publish_to_spectators(&mut self) {
+        let has_spectators = match &mut self.spectators {
+            Some(spectators) => spectators.has_clients(),
+            None => false
+        };
+
+        if !has_spectators {
+            return;
+        }
+
+        let snapshot = take_view_snapshot(self);
+        let simulation_rate = self.timestep.simulation_rate();
+
+        if let Some(spectators) = &mut self.spectators {
+            spectators.publish(snapshot, simulation_rate);
+        }
+    }
+
+    pub fn select_menu_entry(&mut self, ctx: &Context, key: KeyCode) {
+        match self.menu.handle_key(key) {
+            Some(MenuEntry::NewGame) => {
+                //The attract mode demo played with the game rng, the run starts from a fresh seed
+                //Console commands run before the start, like the startup script, still can't be replayed
+                let tainted = self.replay.tainted;
+                self.start_replay();
+                self.replay.tainted = tainted;
+                self.menu.open = false;
+            },
+            Some(MenuEntry::Continue) => match load_snapshot(ctx, self.window.size) {
+                Ok(snapshot) => {
+                    restore_snapshot(self, snapshot);
+                    self.menu.open = false;
+                },
+                Err(error) => {
+                    self.menu.message = error;
+                    self.menu.remove_entry(MenuEntry::Continue);
+                }
+            },
+            Some(MenuEntry::Options) => {
+                self.settings_menu.open = true;
+                self.settings_menu.selected = 0;
+            },
+            None => ()
+        }
+    }
+
+    //Hands the events of the tick to every system listening to the simulation
+    pub fn dispatch_events(&mut self, ctx: &Context) -> GameResult {
+        for event in self.events.drain() {
+            if let Some(outbox) = &mut self.event_outbox {
+                outbox.push(event);
+            }
+
+            hud_handle_event(&mut self.hud, &event);
+            self.stats.handle_event(&event);
+
+            if self.headless {
+                continue;
+            }
+
+            play_event_sound(ctx, &mut self.assets, &event)?;
+
+            match event {
+                GameEvent::WaveCleared{ .. } => save_stats(ctx, &self.stats.stats)?,
+                GameEvent::GameOver{ max_score, .. } => {
+                    save_stats(ctx, &self.stats.stats)?;
+                    save_high_score(ctx, max_score)?;
+
+                    if let Some(replay) = self.finished_replay.take() {
+                        if let Some(path) = &self.record_path {
+                            save_replay_file(path, &replay).map_err(ggez::GameError::CustomError)?;
+                        }
+
+                        if self.autopilot.is_none() {
+                            save_demo_replay(ctx, &replay)?;
+                            self.leaderboard.submit(ctx, replay)?;
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        Ok(())
+    }
+
+    //Keyboard and gamepad presses both end up here
+    pub fn handle_key_down(&mut self, ctx: &mut Context, key: KeyCode, repeated: bool) -> GameResult {
+        if self.menu.open {
+            //Any key ends the attract mode and goes back to the title
+            if self.attract.is_running() {
+                self.attract.stop();
+                return Ok(());
+            }
+
+            self.attract.reset_idle();
+
+            if repeated {
+                return Ok(());
+            }
+
+            if self.settings_menu.open {
+                return handle_settings_key(ctx, self, key);
+            }
+
+            self.select_menu_entry(ctx, key);
+
+            return Ok(());
+        }
+
+        if key == KeyCode::Grave && !repeated {
+            self.console.open = !self.console.open;
+            self.input = InputState::default();
+            return Ok(());
+        }
+
+        if self.console.open {
+            match key {
+                KeyCode::Back => self.console.erase_character(),
+                KeyCode::Return | KeyCode::NumpadEnter => {
+                    let line = self.console.submit();
+                    let message = run_console_line(self, &line);
+
+                    if !message.is_empty() {
+                        self.console.print(message);
+                    }
+                },
+                KeyCode::Escape => self.console.open = false,
+                _ => ()
+            }
+
+            return Ok(());
+        }
+
+        if key == KeyCode::Tab && !repeated {
+            self.show_stats = !self.show_stats;
+
+            if self.show_stats {
+                self.leaderboard.refresh();
+            }
+        }
+
+        if key == SAVE_SNAPSHOT_KEY && !repeated {
+            save_snapshot(ctx, &take_snapshot(self))?;
+            self.console.print(String::from("snapshot saved"));
+            return Ok(());
+        }
+
+        //Held down to scrub quickly
+        if handle_rewind_key(self, key) {
+            return Ok(());
+        }
+
+        if !repeated && self.debug.handle_key(key) {
+            return Ok(());
+        }
+
+        self.input.set_key(key, true, &self.settings.key_bindings);
+
+        Ok(())
+    }
+
+    //The title screen or the game, drawn to the frame or to the offscreen target of the window
+    pub fn draw_frame(&mut self, ctx: &mut Context) -> GameResult {
+        if self.menu.open {
+            return draw_title_screen(ctx, self);
+        }
+
+        let mut canvas = self.window.canvas(ctx, Some(graphics::Color::from([0.0, 0.0, 0.0, 1.0])));
+
+        if self.show_stats {
+            draw_stats_screen(&mut canvas, &self.assets, &self.stats.stats, &self.leaderboard, self.window.size);
+            draw_color_filter(&mut canvas, self.settings.color_filter, self.window.size);
+            canvas.finish(ctx)?;
+            return Ok(());
+        }
+
+        if self.settings.show_fps {
+            self.hud.set_text(HudTag::Fps, format!("FPS {:.0}", ctx.time.fps()));
+        }
+
+        let assets = &mut self.assets;
+        let world_coords = (self.window.size.x, self.window.size.y);
+        let alpha = self.timestep.alpha();
+
+        let p = &self.player;
+        draw_actor(assets, &mut canvas, p, world_coords, alpha);
+
+        for enemie in self.enemies.iter().chain(self.divers.iter()) {
+            let image = assets.enemy_image(enemie, self.enemy_definitions.is_damaged(enemie));
+            draw_actor_image(&mut canvas, image, enemie, world_coords, alpha);
+        }
+
+        if let Some(ufo) = &self.ufo {
+            draw_actor(assets, &mut canvas, ufo, world_coords, alpha);
+        }
+
+        if let Some(boss) = &self.boss {
+            draw_boss(&mut canvas, assets, boss, self.window.size, alpha);
+        }
+
+        for shield in &self.shields {
+            if shield.is_alive() {
+                draw_actor(assets, &mut canvas, shield, world_coords, alpha);
+            }
+        }
+
+        for bullet in &self.player_bullets {
+            draw_actor(assets, &mut canvas, bullet, world_coords, alpha);
+        }
+
+        for bullet in &self.enemy_bullets {
+            draw_actor(assets, &mut canvas, bullet, world_coords, alpha);
+        }
+
+        for powerup in &self.powerups {
+            draw_actor(assets, &mut canvas, powerup, world_coords, alpha);
+
+            if let ActorType::Powerup(powerup_type) = powerup.tag {
+                let letter_size = POWERUP_SIZE.y - 4.0;
+                let letter_center = world_to_screen_coords(world_coords.0, world_coords.1, powerup.draw_position(alpha));
+                let letter_position = letter_center - BitmapFont::text_dimensions(powerup_type.letter(), letter_size) / 2.0;
+                assets.font.draw(&mut canvas, powerup_type.letter(), letter_position, letter_size, graphics::Color::BLACK);
+            }
+        }
+
+        draw_score_popups(&mut canvas, assets, &self.scoring, self.window.size);
+
+        draw_hud(&mut canvas, &self.hud, assets, self.window.size, &self.player, &self.shields);
+
+        if self.settings.cellophane {
+            draw_cellophane(&mut canvas, self.window.size);
+        }
+
+        draw_color_filter(&mut canvas, self.settings.color_filter, self.window.size);
+
+        if self.debug.enabled {
+            draw_debug_overlay(&mut canvas, self, ctx.time.fps());
+        }
+
+        if self.console.open {
+            draw_console(&mut canvas, &self.assets, &self.console, self.window.size);
+        }
+
+        canvas.finish(ctx)
+    }
+
+    //Only the game owning the window post-processes it, the demo game draws into the target of the title screen
+    fn update_offscreen(&mut self, ctx: &Context) -> GameResult {
+        if !self.settings.crt {
+            self.window.target = None;
+            return Ok(());
+        }
+
+        if self.crt.is_none() {
+            self.crt = Some(create_crt_effect(ctx)?);
+        }
+
+        begin_offscreen(ctx, &mut self.window);
+        Ok(())
+    }
+}
+
+impl event::EventHandler for GameState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.leaderboard.poll(ctx)?;
+
+        if self.menu.open && !self.settings_menu.open {
+            return self.attract.update(ctx, &mut self.leaderboard, self.max_score);
+        }
+
+        if self.debug.paused {
+            if self.debug.take_step() {
+                self.step(ctx, self.timestep.step_time)?;
+                self.debug.record_trails(&self.player_bullets, &self.enemy_bullets);
+            }
+
+            return Ok(());
+        }
+
+        self.timestep.advance(ctx.time.delta().as_secs_f32() * self.debug.time_scale());
+
+        while self.timestep.next_step() {
+            self.step(ctx, self.timestep.step_time)?;
+            self.debug.record_trails(&self.player_bullets, &self.enemy_bullets);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        if !self.headless {
+            self.update_offscreen(ctx)?;
+        }
+
+        self.draw_frame(ctx)?;
+
+        if let (false, Some(crt)) = (self.headless, &self.crt) {
+            present_crt(ctx, &self.window, crt)?;
+        }
+
+        timer::yield_now();
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: ggez::input::keyboard::KeyInput, repeated: bool) -> GameResult {
+        match input.keycode {
+            Some(key) => self.handle_key_down(ctx, key, repeated),
+            None => Ok(())
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        if self.console.open {
+            self.console.type_character(character);
+        }
+
+        Ok(())
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        save_stats(ctx, &self.stats.stats)?;
+        save_high_score(ctx, self.max_score.max(self.score))?;
+
+        //A run still in the menu was never started and a bot or replay run is not the player's, the previous save is kept
+        if !self.menu.open && self.autopilot.is_none() {
+            save_snapshot(ctx, &take_snapshot(self))?;
+        }
+
+        Ok(false)
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, input: ggez::input::keyboard::KeyInput) -> GameResult {
+        if let Some(key) = input.keycode {
+            self.input.set_key(key, false, &self.settings.key_bindings);
+        }
+
+        Ok(())
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, button: Button, _id: GamepadId) -> GameResult {
+        match gamepad_key(button, &self.settings.key_bindings, self.menu.open) {
+            Some(key) => self.handle_key_down(ctx, key, false),
+            None => Ok(())
+        }
+    }
+
+    fn gamepad_button_up_event(&mut self, _ctx: &mut Context, button: Button, _id: GamepadId) -> GameResult {
+        if let Some(key) = gamepad_key(button, &self.settings.key_bindings, self.menu.open) {
+            self.input.set_key(key, false, &self.settings.key_bindings);
+        }
+
+        Ok(())
+    }
+
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, axis: Axis, value: f32, _id: GamepadId) -> GameResult {
+        if !self.menu.open && !self.console.open {
+            self.input.set_axis(axis, value);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+
+use crate::actor::{world_to_screen_coords, Actor};
+use crate::assets::Assets;
+use crate::events::GameEvent;
+use crate::font::BitmapFont;
+use crate::lives::{LIFE_ICON_SCALE, LIFE_ICON_SPACING};
+use crate::shields::SHIELD_LIFE;
+
+pub const SMALL_TEXT_SIZE : f32 = 14.0;
+pub const HUD_MARGIN : f32 = 16.0;
+pub const HUD_LINE_OFFSET : f32 = 22.0;
+pub const HUD_ATTACH_SPACING : f32 = 10.0;
+pub const SCORE_DIGITS : usize = 5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HudTag {
+    ScoreLabel,
+    Score,
+    MaxScoreLabel,
+    MaxScore,
+    Powerups,
+    Combo,
+    Lives,
+    Credits,
+    //Shown from the settings
+    Fps,
+    ShieldHp1,
+    ShieldHp2,
+    ShieldHp3,
+}
+
+//Actor a hud element can follow
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ActorRef {
+    Player,
+    Shield(usize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    Actor(ActorRef),
+}
+
+impl Anchor {
+    //Point of the screen the anchor is attached to, None when the followed actor does not exist
+    pub fn resolve(&self, window_size: Vec2, player: &Actor, shields: &[Actor]) -> Option<Vec2> {
+        let point = match self {
+            Anchor::TopLeft => Vec2::new(HUD_MARGIN, HUD_MARGIN),
+            Anchor::TopCenter => Vec2::new(window_size.x / 2.0, HUD_MARGIN),
+            Anchor::TopRight => Vec2::new(window_size.x - HUD_MARGIN, HUD_MARGIN),
+            Anchor::BottomLeft => Vec2::new(HUD_MARGIN, window_size.y - HUD_MARGIN),
+            Anchor::BottomCenter => Vec2::new(window_size.x / 2.0, window_size.y - HUD_MARGIN),
+            Anchor::BottomRight => Vec2::new(window_size.x - HUD_MARGIN, window_size.y - HUD_MARGIN),
+            Anchor::Actor(actor_ref) => {
+                let actor = match actor_ref {
+                    ActorRef::Player => player,
+                    ActorRef::Shield(index) => shields.get(*index)?,
+                };
+
+                let top_center = Vec2::new(actor.position.x, actor.get_rect().top() - HUD_ATTACH_SPACING);
+                world_to_screen_coords(window_size.x, window_size.y, top_center)
+            }
+        };
+
+        Some(point)
+    }
+
+    //Which part of the element is placed on the anchor point, (0, 0) is the top left corner
+    pub fn alignment(&self) -> Vec2 {
+        match self {
+            Anchor::TopLeft => Vec2::new(0.0, 0.0),
+            Anchor::TopCenter => Vec2::new(0.5, 0.0),
+            Anchor::TopRight => Vec2::new(1.0, 0.0),
+            Anchor::BottomLeft => Vec2::new(0.0, 1.0),
+            Anchor::BottomCenter => Vec2::new(0.5, 1.0),
+            Anchor::BottomRight => Vec2::new(1.0, 1.0),
+            Anchor::Actor(_) => Vec2::new(0.5, 1.0),
+        }
+    }
+}
+
+pub enum HudContent {
+    Text(String),
+    LifeIcons,
+}
+
+pub struct HudElement {
+    pub tag: HudTag,
+    pub anchor: Anchor,
+    pub offset: Vec2,
+    pub content: HudContent,
+    pub text_size: f32,
+    pub color: Color,
+    pub visible: bool,
+}
+
+//Hud elements are drawn in the order they are stored
+pub struct Hud {
+    pub elements: Vec<HudElement>,
+}
+
+fn create_text_element(tag: HudTag, anchor: Anchor, offset: Vec2, text: String) -> HudElement {
+    HudElement {
+        tag,
+        anchor,
+        offset,
+        content: HudContent::Text(text),
+        text_size: SMALL_TEXT_SIZE,
+        color: Color::WHITE,
+        visible: true,
+    }
+}
+
+pub fn format_score(score: u32) -> String {
+    format!("{:0width$}", score, width = SCORE_DIGITS)
+}
+
+pub fn create_hud() -> Hud {
+    let line = Vec2::new(0.0, HUD_LINE_OFFSET);
+
+    let mut elements = vec![
+        create_text_element(HudTag::ScoreLabel, Anchor::TopLeft, Vec2::ZERO, String::from("SCORE<1>")),
+        create_text_element(HudTag::Score, Anchor::TopLeft, line, format_score(0)),
+        create_text_element(HudTag::MaxScoreLabel, Anchor::TopCenter, Vec2::ZERO, String::from("HI-SCORE")),
+        create_text_element(HudTag::MaxScore, Anchor::TopCenter, line, format_score(0)),
+        create_text_element(HudTag::Powerups, Anchor::TopRight, Vec2::ZERO, String::new()),
+        create_text_element(HudTag::Combo, Anchor::TopLeft, line * 2.0, String::new()),
+        HudElement {
+            tag: HudTag::Lives,
+            anchor: Anchor::BottomLeft,
+            offset: Vec2::ZERO,
+            content: HudContent::LifeIcons,
+            text_size: SMALL_TEXT_SIZE,
+            color: Color::WHITE,
+            visible: true,
+        },
+        create_text_element(HudTag::Credits, Anchor::BottomRight, Vec2::ZERO, String::from("CREDIT 00")),
+        HudElement {
+            visible: false,
+            ..create_text_element(HudTag::Fps, Anchor::BottomCenter, Vec2::ZERO, String::new())
+        },
+    ];
+
+    for (i, tag) in [HudTag::ShieldHp1, HudTag::ShieldHp2, HudTag::ShieldHp3].iter().enumerate() {
+        elements.push(create_text_element(*tag, Anchor::Actor(ActorRef::Shield(i)), Vec2::ZERO, format!("{}", SHIELD_LIFE)));
+    }
+
+    Hud { elements }
+}
+
+impl Hud {
+    pub fn element_mut(&mut self, tag: HudTag) -> Option<&mut HudElement> {
+        self.elements.iter_mut().find(|element| element.tag == tag)
+    }
+
+    pub fn set_text(&mut self, tag: HudTag, text: String) {
+        if let Some(element) = self.element_mut(tag) {
+            element.content = HudContent::Text(text);
+        }
+    }
+
+    pub fn set_visible(&mut self, tag: HudTag, visible: bool) {
+        if let Some(element) = self.element_mut(tag) {
+            element.visible = visible;
+        }
+    }
+
+    pub fn reset_shield_elements(&mut self) {
+        for tag in [HudTag::ShieldHp1, HudTag::ShieldHp2, HudTag::ShieldHp3] {
+            self.set_text(tag, format!("{}", SHIELD_LIFE));
+            self.set_visible(tag, true);
+        }
+    }
+}
+
+fn life_icons_dimensions(image: &graphics::Image, lives: f32) -> Vec2 {
+    let icon_size = Vec2::new(image.width() as f32, image.height() as f32) * LIFE_ICON_SCALE;
+    let count = lives.max(0.0) as u32;
+
+    if count == 0 {
+        return Vec2::new(0.0, icon_size.y);
+    }
+
+    Vec2::new(count as f32 * (icon_size.x + LIFE_ICON_SPACING) - LIFE_ICON_SPACING, icon_size.y)
+}
+
+//Draws one small ship icon per life with the top left corner of the first one at dest
+fn draw_life_icons(canvas: &mut graphics::Canvas, image: &graphics::Image, lives: f32, dest: Vec2) {
+    let icon_width = image.width() as f32 * LIFE_ICON_SCALE;
+
+    for i in 0..lives.max(0.0) as u32 {
+        let drawparams = graphics::DrawParam::new()
+            .dest(dest + Vec2::new(i as f32 * (icon_width + LIFE_ICON_SPACING), 0.0))
+            .scale(Vec2::new(LIFE_ICON_SCALE, LIFE_ICON_SCALE));
+
+        canvas.draw(image, drawparams);
+    }
+}
+
+pub fn shield_hud_tag(shied_index: usize) -> Option<HudTag> {
+    match shied_index {
+        0 => {
+            Some(HudTag::ShieldHp1)
+        }
+        1 => {
+            Some(HudTag::ShieldHp2)
+        }
+        2 => {
+            Some(HudTag::ShieldHp3)
+        }
+        _ => { None }
+    }
+}
+
+//Keeps the hud in sync with the simulation
+pub fn hud_handle_event(hud: &mut Hud, event: &GameEvent) {
+    match *event {
+        GameEvent::ScoreChanged{ score } => hud.set_text(HudTag::Score, format_score(score)),
+        GameEvent::ShieldDamaged{ index, hp } | GameEvent::ShieldRepaired{ index, hp } => {
+            if let Some(tag) = shield_hud_tag(index) {
+                if hp <= 0.0 {
+                    hud.set_visible(tag, false);
+                }
+                else {
+                    hud.set_text(tag, format!("{}", hp));
+                }
+            }
+        },
+        GameEvent::GameOver{ max_score, .. } => {
+            hud.reset_shield_elements();
+            hud.set_text(HudTag::Powerups, String::new());
+            hud.set_text(HudTag::MaxScore, format_score(max_score));
+        },
+        _ => ()
+    }
+}
+
+pub fn draw_hud(canvas: &mut graphics::Canvas, hud: &Hud, assets: &Assets, window_size: Vec2, player: &Actor, shields: &[Actor]) {
+    canvas.set_sampler(graphics::Sampler::nearest_clamp());
+
+    for element in &hud.elements {
+        if !element.visible {
+            continue;
+        }
+
+        let anchor_point = match element.anchor.resolve(window_size, player, shields) {
+            Some(point) => point,
+            None => continue,
+        };
+
+        let dimensions = match &element.content {
+            HudContent::Text(text) => BitmapFont::text_dimensions(text, element.text_size),
+            HudContent::LifeIcons => life_icons_dimensions(&assets.player_image, player.hp),
+        };
+
+        let top_left = anchor_point + element.offset - dimensions * element.anchor.alignment();
+
+        match &element.content {
+            HudContent::Text(text) => assets.font.draw(canvas, text, top_left, element.text_size, element.color),
+            HudContent::LifeIcons => draw_life_icons(canvas, &assets.player_image, player.hp, top_left),
+        }
+    }
+
+    canvas.set_sampler(graphics::Sampler::default());
+}
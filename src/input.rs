@@ -0,0 +1,140 @@
+use ggez::input::gamepad::gilrs::{Axis, Button};
+use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+
+pub const INPUT_LEFT : u8 = 1;
+pub const INPUT_RIGHT : u8 = 2;
+pub const INPUT_FIRE : u8 = 4;
+//How far the stick is pushed before it counts as a direction
+pub const GAMEPAD_STICK_DEADZONE : f32 = 0.5;
+
+//Keys the controls can be bound to, saved by name in the settings
+pub const BINDABLE_KEYS : [KeyCode; 48] = [
+    KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down, KeyCode::Space,
+    KeyCode::LControl, KeyCode::RControl, KeyCode::LShift, KeyCode::RShift, KeyCode::LAlt, KeyCode::RAlt,
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Return,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key).to_uppercase()
+}
+
+//KeyCode has no serde support, the bindings are saved as key names
+mod key_serde {
+    use ggez::input::keyboard::KeyCode;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{key_name, BINDABLE_KEYS};
+
+    pub fn serialize<S: Serializer>(key: &KeyCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&key_name(*key))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KeyCode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        BINDABLE_KEYS.iter()
+            .find(|key| key_name(**key) == name)
+            .copied()
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown key {}", name)))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    #[serde(with = "key_serde")]
+    pub left: KeyCode,
+    #[serde(with = "key_serde")]
+    pub right: KeyCode,
+    #[serde(with = "key_serde")]
+    pub fire: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            fire: KeyCode::Space,
+        }
+    }
+}
+
+//The key a gamepad button stands for, south confirms in the menus and fires in the game
+pub fn gamepad_key(button: Button, bindings: &KeyBindings, in_menu: bool) -> Option<KeyCode> {
+    match button {
+        Button::DPadLeft if in_menu => Some(KeyCode::Left),
+        Button::DPadRight if in_menu => Some(KeyCode::Right),
+        Button::DPadLeft => Some(bindings.left),
+        Button::DPadRight => Some(bindings.right),
+        Button::DPadUp => Some(KeyCode::Up),
+        Button::DPadDown => Some(KeyCode::Down),
+        Button::South if in_menu => Some(KeyCode::Return),
+        Button::South => Some(bindings.fire),
+        Button::East => Some(KeyCode::Escape),
+        Button::Start => Some(KeyCode::Return),
+        Button::Select => Some(KeyCode::Tab),
+        _ => None
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct InputState {
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+}
+
+impl InputState {
+    pub fn set_key(&mut self, key: KeyCode, pressed: bool, bindings: &KeyBindings) {
+        if key == bindings.left {
+            self.left = pressed;
+        }
+
+        if key == bindings.right {
+            self.right = pressed;
+        }
+
+        if key == bindings.fire {
+            self.fire = pressed;
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: Axis, value: f32) {
+        if axis == Axis::LeftStickX {
+            self.left = value < -GAMEPAD_STICK_DEADZONE;
+            self.right = value > GAMEPAD_STICK_DEADZONE;
+        }
+    }
+
+    //Packs the state in a byte for the replays
+    pub fn to_bits(&self) -> u8 {
+        let mut bits = 0;
+
+        if self.left {
+            bits |= INPUT_LEFT;
+        }
+
+        if self.right {
+            bits |= INPUT_RIGHT;
+        }
+
+        if self.fire {
+            bits |= INPUT_FIRE;
+        }
+
+        bits
+    }
+
+    pub fn from_bits(bits: u8) -> InputState {
+        InputState {
+            left: bits & INPUT_LEFT != 0,
+            right: bits & INPUT_RIGHT != 0,
+            fire: bits & INPUT_FIRE != 0,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::replay::Replay;
+use crate::stats::{read_user_file, write_user_file};
+
+//endpoint=http://host:port/path and name=PLAYER, one per line
+pub const LEADERBOARD_CONFIG_FILE : &str = "/leaderboard.txt";
+//Submissions made while the server could not be reached
+pub const LEADERBOARD_QUEUE_FILE : &str = "/leaderboard_queue.json";
+pub const DEFAULT_LEADERBOARD_ENDPOINT : &str = "http://127.0.0.1:7878";
+pub const DEFAULT_PLAYER_NAME : &str = "PLAYER";
+//Scores shown on the stats screen
+pub const LEADERBOARD_SIZE : usize = 5;
+pub const HTTP_TIMEOUT : Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: u32,
+}
+
+//The replay lets the server check the score by playing the run again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub name: String,
+    pub score: u32,
+    pub replay: Replay,
+    //Finds the submission in the queue when its answer comes back
+    #[serde(skip)]
+    pub id: u64,
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    //Kept in the queue and sent again later
+    Offline(String),
+    //Refused by the server, dropped
+    Rejected(String),
+}
+
+//Answers coming back from the request threads
+#[derive(Debug)]
+pub enum LeaderboardResponse {
+    Submitted(Submission, Result<(), SubmitError>),
+    Top(Result<Vec<LeaderboardEntry>, String>),
+}
+
+//Requests run on their own threads, poll collects the answers every frame
+#[derive(Debug)]
+pub struct LeaderboardClient {
+    pub endpoint: String,
+    pub name: String,
+    //Every submission stays on disk until the server accepts or refuses it
+    pub queue: Vec<Submission>,
+    //Ids of the queued submissions waiting for an answer
+    pub sending: Vec<u64>,
+    pub next_id: u64,
+    pub top: Vec<LeaderboardEntry>,
+    //Last thing that happened, shown under the scores
+    pub status: String,
+    pub sender: Sender<LeaderboardResponse>,
+    pub receiver: Receiver<LeaderboardResponse>,
+}
+
+//Client of the headless games, it has no endpoint and nothing queued
+pub fn create_offline_leaderboard_client() -> LeaderboardClient {
+    let (sender, receiver) = channel();
+
+    LeaderboardClient {
+        endpoint: String::new(),
+        name: String::from(DEFAULT_PLAYER_NAME),
+        queue: Vec::new(),
+        sending: Vec::new(),
+        next_id: 0,
+        top: Vec::new(),
+        status: String::new(),
+        sender,
+        receiver,
+    }
+}
+
+pub fn load_leaderboard_client(ctx: &Context) -> LeaderboardClient {
+    let mut endpoint = String::from(DEFAULT_LEADERBOARD_ENDPOINT);
+    let mut name = String::from(DEFAULT_PLAYER_NAME);
+
+    for line in read_user_file(ctx, LEADERBOARD_CONFIG_FILE).unwrap_or_default().lines() {
+        match line.split_once('=') {
+            Some(("endpoint", value)) => endpoint = value.trim().to_string(),
+            Some(("name", value)) => name = value.trim().to_string(),
+            _ => ()
+        }
+    }
+
+    let mut queue: Vec<Submission> = read_user_file(ctx, LEADERBOARD_QUEUE_FILE)
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+
+    for (id, submission) in queue.iter_mut().enumerate() {
+        submission.id = id as u64;
+    }
+
+    LeaderboardClient {
+        endpoint,
+        name,
+        next_id: queue.len() as u64,
+        queue,
+        ..create_offline_leaderboard_client()
+    }
+}
+
+impl LeaderboardClient {
+    //The submission is queued on disk first so quitting before the answer doesn't lose it
+    pub fn submit(&mut self, ctx: &Context, replay: Replay) -> GameResult {
+        let submission = Submission {
+            name: self.name.clone(),
+            score: replay.score,
+            replay,
+            id: self.next_id,
+        };
+
+        self.next_id += 1;
+        self.queue.push(submission.clone());
+        self.save_queue(ctx)?;
+        self.send(submission);
+
+        Ok(())
+    }
+
+    fn send(&mut self, submission: Submission) {
+        let endpoint = self.endpoint.clone();
+        let sender = self.sender.clone();
+
+        self.sending.push(submission.id);
+
+        thread::spawn(move || {
+            let result = post_submission(&endpoint, &submission);
+            let _ = sender.send(LeaderboardResponse::Submitted(submission, result));
+        });
+    }
+
+    //Sends the queued submissions again and asks for the best scores
+    pub fn refresh(&mut self) {
+        let waiting: Vec<Submission> = self.queue.iter()
+            .filter(|submission| !self.sending.contains(&submission.id))
+            .cloned()
+            .collect();
+
+        for submission in waiting {
+            self.send(submission);
+        }
+
+        let endpoint = self.endpoint.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let _ = sender.send(LeaderboardResponse::Top(fetch_top(&endpoint, LEADERBOARD_SIZE)));
+        });
+    }
+
+    pub fn poll(&mut self, ctx: &Context) -> GameResult {
+        let mut queue_changed = false;
+
+        while let Ok(response) = self.receiver.try_recv() {
+            if let LeaderboardResponse::Submitted(submission, _) = &response {
+                self.sending.retain(|id| *id != submission.id);
+            }
+
+            match response {
+                LeaderboardResponse::Submitted(submission, Ok(())) => {
+                    self.status = format!("score {} submitted", submission.score);
+                    self.queue.retain(|queued| queued.id != submission.id);
+                    queue_changed = true;
+                },
+                //Still in the queue, sent again at the next refresh
+                LeaderboardResponse::Submitted(submission, Err(SubmitError::Offline(error))) => self.status = format!("offline, score {} queued: {}", submission.score, error),
+                LeaderboardResponse::Submitted(submission, Err(SubmitError::Rejected(error))) => {
+                    self.status = format!("score {} rejected: {}", submission.score, error);
+                    self.queue.retain(|queued| queued.id != submission.id);
+                    queue_changed = true;
+                },
+                LeaderboardResponse::Top(Ok(top)) => self.top = top,
+                LeaderboardResponse::Top(Err(error)) => self.status = format!("leaderboard offline: {}", error),
+            }
+        }
+
+        if queue_changed {
+            self.save_queue(ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_queue(&self, ctx: &Context) -> GameResult {
+        let text = serde_json::to_string(&self.queue).map_err(|error| ggez::GameError::CustomError(error.to_string()))?;
+        write_user_file(ctx, LEADERBOARD_QUEUE_FILE, &text)
+    }
+}
+
+pub fn leaderboard_text(client: &LeaderboardClient) -> String {
+    let mut lines = vec![String::from("LEADERBOARD")];
+
+    for (i, entry) in client.top.iter().enumerate() {
+        lines.push(format!("{}. {:<12} {}", i + 1, entry.name.to_uppercase(), entry.score));
+    }
+
+    if client.top.is_empty() {
+        lines.push(String::from("NO SCORES"));
+    }
+
+    if !client.queue.is_empty() {
+        lines.push(format!("{} SCORES WAITING TO BE SENT", client.queue.len()));
+    }
+
+    if !client.status.is_empty() {
+        lines.push(client.status.to_uppercase());
+    }
+
+    lines.join("\n")
+}
+
+fn post_submission(endpoint: &str, submission: &Submission) -> Result<(), SubmitError> {
+    let body = serde_json::to_string(submission).map_err(|error| SubmitError::Rejected(error.to_string()))?;
+
+    match http_request(endpoint, "POST", "/scores", &body) {
+        Ok((status, _)) if (200..300).contains(&status) => Ok(()),
+        Ok((status, body)) if status >= 500 => Err(SubmitError::Offline(format!("{} {}", status, body.trim()))),
+        Ok((status, body)) => Err(SubmitError::Rejected(format!("{} {}", status, body.trim()))),
+        Err(error) => Err(SubmitError::Offline(error))
+    }
+}
+
+fn fetch_top(endpoint: &str, count: usize) -> Result<Vec<LeaderboardEntry>, String> {
+    match http_request(endpoint, "GET", &format!("/scores?limit={}", count), "")? {
+        (200, body) => serde_json::from_str(&body).map_err(|error| error.to_string()),
+        (status, body) => Err(format!("{} {}", status, body.trim()))
+    }
+}
+
+//Splits http://host:port/base into the address and the base path
+fn parse_endpoint(endpoint: &str) -> Result<(String, String), String> {
+    let rest = endpoint.strip_prefix("http://").ok_or_else(|| format!("only http endpoints are supported: {}", endpoint))?;
+
+    let (host, base) = match rest.split_once('/') {
+        Some((host, base)) => (host, format!("/{}", base.trim_end_matches('/'))),
+        None => (rest, String::new())
+    };
+
+    let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+    Ok((address, base))
+}
+
+//Enough HTTP/1.1 for the leaderboard, the connection is closed after one request and bodies are not chunked
+pub fn http_request(endpoint: &str, method: &str, path: &str, body: &str) -> Result<(u16, String), String> {
+    let (address, base) = parse_endpoint(endpoint)?;
+
+    let socket_address = address.to_socket_addrs()
+        .map_err(|error| error.to_string())?
+        .next()
+        .ok_or_else(|| format!("can't resolve {}", address))?;
+
+    let mut stream = TcpStream::connect_timeout(&socket_address, HTTP_TIMEOUT).map_err(|error| error.to_string())?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT)).map_err(|error| error.to_string())?;
+    stream.set_write_timeout(Some(HTTP_TIMEOUT)).map_err(|error| error.to_string())?;
+
+    let request = format!("{} {}{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method, base, path, address, body.len(), body);
+    stream.write_all(request.as_bytes()).map_err(|error| error.to_string())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|error| error.to_string())?;
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("malformed response")?;
+    let status = head.split_whitespace().nth(1).and_then(|status| status.parse().ok()).ok_or("malformed status line")?;
+
+    Ok((status, body.to_string()))
+}
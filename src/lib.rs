@@ -0,0 +1,45 @@
+//! Galactic Strike - a basic Space Invaders clone.
+//!
+//! The game logic lives in this library so it can be reused by other tools,
+//! the `galactic_strike` binary only wires the `GameState` to ggez.
+#![allow(clippy::unnecessary_wraps)]
+
+pub mod actor;
+pub mod assets;
+pub mod attract;
+pub mod audio;
+pub mod boss;
+pub mod bot;
+pub mod classic;
+pub mod cli;
+pub mod collision;
+pub mod console;
+pub mod debug;
+pub mod effects;
+pub mod enemies;
+pub mod events;
+pub mod font;
+pub mod game;
+pub mod hud;
+pub mod input;
+pub mod leaderboard;
+pub mod lives;
+pub mod menu;
+pub mod netplay;
+pub mod powerups;
+pub mod replay;
+pub mod rewind;
+pub mod rng;
+pub mod ruleset;
+pub mod scoring;
+pub mod settings;
+pub mod shields;
+pub mod snapshot;
+pub mod spectator;
+pub mod stats;
+pub mod timestep;
+pub mod ufo;
+pub mod versus;
+pub mod weapon;
+
+pub use game::{GameState, Window};
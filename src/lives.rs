@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::actor::Actor;
+
+pub const MAX_PLAYER_LIFE : f32 = 6.0;
+pub const FIRST_EXTRA_LIFE_SCORE : u32 = 1500;
+pub const LIFE_ICON_SCALE : f32 = 0.5;
+pub const LIFE_ICON_SPACING : f32 = 6.0;
+
+//Score thresholds at which the player is awarded an extra life
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct ExtraLifeSettings {
+    pub first_score: u32,
+    pub every_score: Option<u32>,
+    pub max_lives: f32,
+}
+
+impl Default for ExtraLifeSettings {
+    fn default() -> Self {
+        ExtraLifeSettings {
+            first_score: FIRST_EXTRA_LIFE_SCORE,
+            every_score: None,
+            max_lives: MAX_PLAYER_LIFE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraLifeAwards {
+    pub settings: ExtraLifeSettings,
+    pub next_score: Option<u32>,
+}
+
+pub fn create_extra_life_awards(settings: ExtraLifeSettings) -> ExtraLifeAwards {
+    ExtraLifeAwards {
+        settings,
+        next_score: Some(settings.first_score),
+    }
+}
+
+impl ExtraLifeAwards {
+    //Returns how many thresholds the score has passed since the last check
+    pub fn check(&mut self, score: u32) -> u32 {
+        let mut awards = 0;
+
+        while let Some(next_score) = self.next_score {
+            if score < next_score {
+                break;
+            }
+
+            awards += 1;
+            //A threshold past u32::MAX can't be reached, so the awards stop there
+            self.next_score = match self.settings.every_score {
+                Some(every_score) if every_score > 0 => next_score.checked_add(every_score),
+                _ => None
+            };
+        }
+
+        awards
+    }
+
+    pub fn reset(&mut self) {
+        self.next_score = Some(self.settings.first_score);
+    }
+}
+
+//Adds a life without going over the cap, returns false when the player already has max lives
+pub fn add_life(player: &mut Actor, max_lives: f32) -> bool {
+    if player.hp >= max_lives {
+        return false;
+    }
+
+    player.hp = (player.hp + 1.0).min(max_lives);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::create_player;
+
+    fn awards(first_score: u32, every_score: Option<u32>) -> ExtraLifeAwards {
+        create_extra_life_awards(ExtraLifeSettings { first_score, every_score, max_lives: MAX_PLAYER_LIFE })
+    }
+
+    #[test]
+    fn single_award_is_given_once() {
+        let mut awards = awards(1500, None);
+
+        assert_eq!(awards.check(1499), 0);
+        assert_eq!(awards.check(1500), 1);
+        assert_eq!(awards.check(1500), 0);
+        assert_eq!(awards.check(90000), 0);
+        assert_eq!(awards.next_score, None);
+    }
+
+    #[test]
+    fn repeating_awards_count_every_threshold_passed() {
+        let mut awards = awards(1000, Some(2000));
+
+        assert_eq!(awards.check(999), 0);
+        assert_eq!(awards.check(1000), 1);
+        assert_eq!(awards.check(2999), 0);
+        //A big jump passes several thresholds at once
+        assert_eq!(awards.check(7000), 3);
+        assert_eq!(awards.next_score, Some(9000));
+    }
+
+    #[test]
+    fn zero_interval_only_gives_the_first_award() {
+        let mut awards = awards(500, Some(0));
+
+        assert_eq!(awards.check(100000), 1);
+        assert_eq!(awards.check(200000), 0);
+    }
+
+    #[test]
+    fn awards_stop_at_the_largest_score() {
+        let mut awards = awards(u32::MAX - 10, Some(u32::MAX / 2));
+
+        assert_eq!(awards.check(u32::MAX), 1);
+        assert_eq!(awards.next_score, None);
+        assert_eq!(awards.check(u32::MAX), 0);
+    }
+
+    #[test]
+    fn reset_starts_from_the_first_threshold() {
+        let mut awards = awards(1000, Some(1000));
+
+        assert_eq!(awards.check(3500), 3);
+        awards.reset();
+        assert_eq!(awards.check(1000), 1);
+    }
+
+    #[test]
+    fn lives_are_capped() {
+        let mut player = create_player();
+        player.hp = MAX_PLAYER_LIFE - 1.0;
+
+        assert!(add_life(&mut player, MAX_PLAYER_LIFE));
+        assert_eq!(player.hp, MAX_PLAYER_LIFE);
+        assert!(!add_life(&mut player, MAX_PLAYER_LIFE));
+        assert_eq!(player.hp, MAX_PLAYER_LIFE);
+
+        player.hp = 5.5;
+        assert!(add_life(&mut player, MAX_PLAYER_LIFE));
+        assert_eq!(player.hp, MAX_PLAYER_LIFE);
+    }
+}
@@ -0,0 +1,99 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::input::keyboard::KeyCode;
+
+use crate::assets::Assets;
+use crate::font::BitmapFont;
+
+pub const MENU_TEXT_SIZE : f32 = 18.0;
+pub const MENU_TITLE_SIZE : f32 = 32.0;
+pub const MENU_MESSAGE_SIZE : f32 = 12.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MenuEntry {
+    NewGame,
+    Continue,
+    Options,
+}
+
+impl MenuEntry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MenuEntry::NewGame => "NEW GAME",
+            MenuEntry::Continue => "CONTINUE",
+            MenuEntry::Options => "OPTIONS",
+        }
+    }
+}
+
+//Shown at launch, the simulation waits while it is open
+#[derive(Debug)]
+pub struct MainMenu {
+    pub open: bool,
+    pub entries: Vec<MenuEntry>,
+    pub selected: usize,
+    //Why the last entry could not be used
+    pub message: String,
+}
+
+pub fn create_main_menu(can_continue: bool) -> MainMenu {
+    let entries = if can_continue {
+        vec![MenuEntry::Continue, MenuEntry::NewGame, MenuEntry::Options]
+    }
+    else {
+        vec![MenuEntry::NewGame, MenuEntry::Options]
+    };
+
+    MainMenu {
+        open: true,
+        entries,
+        selected: 0,
+        message: String::new(),
+    }
+}
+
+impl MainMenu {
+    //Returns the entry chosen with the key
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<MenuEntry> {
+        match key {
+            KeyCode::Up => self.selected = (self.selected + self.entries.len() - 1) % self.entries.len(),
+            KeyCode::Down => self.selected = (self.selected + 1) % self.entries.len(),
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => return self.entries.get(self.selected).copied(),
+            _ => ()
+        }
+
+        None
+    }
+
+    //Drops an entry that can't be used anymore, like a rejected save
+    pub fn remove_entry(&mut self, entry: MenuEntry) {
+        self.entries.retain(|menu_entry| *menu_entry != entry);
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+}
+
+pub fn draw_main_menu(canvas: &mut graphics::Canvas, assets: &Assets, menu: &MainMenu, window_size: Vec2) {
+    canvas.set_sampler(graphics::Sampler::nearest_clamp());
+
+    let title = "GALACTIC STRIKE";
+    let title_dimensions = BitmapFont::text_dimensions(title, MENU_TITLE_SIZE);
+    let title_position = Vec2::new((window_size.x - title_dimensions.x) / 2.0, window_size.y / 4.0);
+    assets.font.draw(canvas, title, title_position, MENU_TITLE_SIZE, Color::WHITE);
+
+    let mut y = window_size.y / 2.0;
+
+    for (i, entry) in menu.entries.iter().enumerate() {
+        let text = if i == menu.selected { format!("> {} <", entry.name()) } else { entry.name().to_string() };
+        let dimensions = BitmapFont::text_dimensions(&text, MENU_TEXT_SIZE);
+        let color = if i == menu.selected { Color::YELLOW } else { Color::WHITE };
+
+        assets.font.draw(canvas, &text, Vec2::new((window_size.x - dimensions.x) / 2.0, y), MENU_TEXT_SIZE, color);
+        y += dimensions.y * 2.0;
+    }
+
+    if !menu.message.is_empty() {
+        let message = menu.message.to_uppercase();
+        let dimensions = BitmapFont::text_dimensions(&message, MENU_MESSAGE_SIZE);
+        assets.font.draw(canvas, &message, Vec2::new((window_size.x - dimensions.x) / 2.0, y + dimensions.y), MENU_MESSAGE_SIZE, Color::RED);
+    }
+}
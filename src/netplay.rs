@@ -0,0 +1,476 @@
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use ggez::glam::*;
+use serde::{Deserialize, Serialize};
+
+//Bumped every time the messages or the versus simulation change
+pub const NETPLAY_VERSION : u32 = 1;
+//Local inputs are played this many ticks after they are read, it hides most of the latency
+pub const INPUT_DELAY : u32 = 2;
+//Ticks simulated past the last remote input received before waiting for it
+pub const MAX_ROLLBACK : u32 = 8;
+//Inputs sent again in every packet at most, lost packets are covered by the next ones
+pub const MAX_INPUTS_PER_PACKET : usize = 64;
+pub const MAX_PACKET_SIZE : usize = 2048;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NetMessage {
+    //Sent until the other side starts playing, the match uses the seed of player 0
+    Hello{ version: u32, seed: u64, window_size: Vec2 },
+    //Inputs of the sender from first_tick on, ack is how many inputs of the receiver it already has
+    Inputs{ first_tick: u32, inputs: Vec<u8>, ack: u32 },
+}
+
+//Exchanges the InputState bits of every tick with the other player
+#[derive(Debug)]
+pub struct NetSession {
+    pub socket: UdpSocket,
+    pub remote: SocketAddr,
+    //0 or 1, both sides simulate the two games in the order of this index
+    pub player_index: usize,
+    pub seed: u64,
+    pub window_size: Vec2,
+    //Set once the hello of the other side has been accepted
+    pub match_seed: Option<u64>,
+    //Why the other side can't be played against
+    pub error: Option<String>,
+    //Indexed by tick, the first INPUT_DELAY ticks are empty inputs
+    pub local_inputs: Vec<u8>,
+    //Inputs received from the other side, without gaps from tick 0
+    pub remote_inputs: Vec<u8>,
+    //Local inputs the other side has received
+    pub remote_ack: u32,
+    //The other side has sent inputs, so it got the hello
+    pub remote_started: bool,
+}
+
+pub fn create_net_session(local_address: &str, remote_address: &str, window_size: Vec2) -> std::io::Result<NetSession> {
+    let socket = UdpSocket::bind(local_address)?;
+    socket.set_nonblocking(true)?;
+
+    let remote = remote_address.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, format!("can't resolve {}", remote_address)))?;
+
+    let player_index = if socket.local_addr()? < remote { 0 } else { 1 };
+
+    Ok(NetSession {
+        socket,
+        remote,
+        player_index,
+        seed: rand::random(),
+        window_size,
+        match_seed: None,
+        error: None,
+        local_inputs: vec![0; INPUT_DELAY as usize],
+        remote_inputs: Vec::new(),
+        remote_ack: 0,
+        remote_started: false,
+    })
+}
+
+impl NetSession {
+    fn send(&self, message: &NetMessage) {
+        //UDP, a lost packet is sent again with the next one
+        if let Ok(bytes) = serde_json::to_vec(message) {
+            let _ = self.socket.send_to(&bytes, self.remote);
+        }
+    }
+
+    pub fn send_hello(&self) {
+        self.send(&NetMessage::Hello{ version: NETPLAY_VERSION, seed: self.seed, window_size: self.window_size });
+    }
+
+    //Local inputs the other side doesn't have yet
+    pub fn inputs_message(&self) -> NetMessage {
+        let first_tick = self.remote_ack.min(self.local_inputs.len() as u32);
+        let last_tick = (first_tick as usize + MAX_INPUTS_PER_PACKET).min(self.local_inputs.len());
+
+        NetMessage::Inputs{
+            first_tick,
+            inputs: self.local_inputs[first_tick as usize..last_tick].to_vec(),
+            ack: self.remote_inputs.len() as u32,
+        }
+    }
+
+    pub fn send_inputs(&self) {
+        self.send(&self.inputs_message());
+    }
+
+    //Input of the local player for the tick INPUT_DELAY ticks ahead of the one being simulated
+    pub fn push_local_input(&mut self, tick: u32, bits: u8) {
+        while self.local_inputs.len() <= (tick + INPUT_DELAY) as usize {
+            self.local_inputs.push(bits);
+        }
+    }
+
+    pub fn is_confirmed(&self, tick: u32) -> bool {
+        (tick as usize) < self.remote_inputs.len()
+    }
+
+    //The real input when it arrived, otherwise the last one received is predicted to still be held
+    pub fn remote_input(&self, tick: u32) -> u8 {
+        match self.remote_inputs.get(tick as usize) {
+            Some(bits) => *bits,
+            None => self.remote_inputs.last().copied().unwrap_or(0)
+        }
+    }
+
+    //Reads every waiting packet
+    pub fn receive(&mut self) {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+
+        loop {
+            let (size, sender) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                //The other side is not listening yet
+                Err(error) if error.kind() == ErrorKind::ConnectionRefused => continue,
+                Err(_) => break
+            };
+
+            if sender != self.remote {
+                continue;
+            }
+
+            if let Ok(message) = serde_json::from_slice(&buffer[..size]) {
+                self.handle_message(message);
+            }
+        }
+    }
+
+    pub fn handle_message(&mut self, message: NetMessage) {
+        match message {
+            NetMessage::Hello{ version, seed, window_size } => {
+                if version != NETPLAY_VERSION {
+                    self.error = Some(format!("opponent runs version {}", version));
+                }
+                else if window_size != self.window_size {
+                    self.error = Some(format!("opponent window is {}x{}", window_size.x, window_size.y));
+                }
+                else if self.match_seed.is_none() {
+                    self.match_seed = Some(if self.player_index == 0 { self.seed } else { seed });
+                }
+            },
+            NetMessage::Inputs{ first_tick, inputs, ack } => {
+                self.remote_started = true;
+                self.remote_ack = self.remote_ack.max(ack);
+
+                for (i, bits) in inputs.into_iter().enumerate() {
+                    if first_tick as usize + i == self.remote_inputs.len() {
+                        self.remote_inputs.push(bits);
+                    }
+                }
+            }
+        }
+    }
+}
+
+//State at the start of a tick played with a predicted remote input
+#[derive(Debug, Clone)]
+pub struct TickState<S> {
+    pub tick: u32,
+    pub state: S,
+    pub remote_input: u8,
+}
+
+//Ticks played ahead of the remote inputs, played again when a prediction turns out wrong
+#[derive(Debug)]
+pub struct Rollback<S> {
+    //Next tick to simulate
+    pub tick: u32,
+    //Ticks that can still be rolled back, oldest first
+    pub history: VecDeque<TickState<S>>,
+}
+
+pub fn create_rollback<S>() -> Rollback<S> {
+    Rollback {
+        tick: 0,
+        history: VecDeque::new(),
+    }
+}
+
+impl<S> Rollback<S> {
+    //Too far ahead of the opponent, its inputs have to arrive first
+    pub fn is_waiting(&self, session: &NetSession) -> bool {
+        self.tick >= session.remote_inputs.len() as u32 + MAX_ROLLBACK
+    }
+
+    //Remembers the state at the start of the tick about to be played, returns the inputs of the tick in player order
+    pub fn begin_tick(&mut self, session: &NetSession, state: S) -> [u8; 2] {
+        let local_input = session.local_inputs[self.tick as usize];
+        let remote_input = session.remote_input(self.tick);
+
+        self.history.push_back(TickState{ tick: self.tick, state, remote_input });
+
+        if session.player_index == 0 { [local_input, remote_input] } else { [remote_input, local_input] }
+    }
+
+    pub fn end_tick(&mut self) {
+        self.tick += 1;
+    }
+
+    //Removes the ticks from the first one whose predicted remote input turned out wrong, returns the state it started from
+    pub fn take_mispredicted(&mut self, session: &NetSession) -> Option<TickState<S>> {
+        let mispredicted = self.history.iter().position(|state| {
+            session.is_confirmed(state.tick) && session.remote_input(state.tick) != state.remote_input
+        });
+
+        match mispredicted {
+            Some(index) => self.history.split_off(index).pop_front(),
+            None => None
+        }
+    }
+
+    //Ticks played with the real remote input can't be rolled back anymore
+    pub fn forget_confirmed(&mut self, session: &NetSession) {
+        while self.history.front().is_some_and(|state| session.is_confirmed(state.tick)) {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::rng::{game_rng, game_rng_state, restore_game_rng, seed_game_rng, GameRngState};
+
+    const WINDOW_SIZE : Vec2 = Vec2::new(650.0, 700.0);
+    const MATCH_TICKS : u32 = 300;
+
+    //Stand-in for the two games, every tick mixes the inputs with a number of the game rng
+    #[derive(Debug, Default, Clone, PartialEq)]
+    struct ToyMatch {
+        positions: [i64; 2],
+        checksum: u64,
+    }
+
+    fn toy_step(state: &mut ToyMatch, inputs: [u8; 2]) {
+        for (player, bits) in inputs.iter().enumerate() {
+            state.positions[player] += match bits & 3 { 1 => -1, 2 => 1, _ => 0 };
+        }
+
+        let mixed = game_rng().next_u64() ^ ((inputs[0] as u64) << 8) ^ ((inputs[1] as u64) << 16);
+        state.checksum = (state.checksum ^ mixed).rotate_left(5).wrapping_mul(0x100000001b3);
+    }
+
+    //Input held by a player, it changes every few ticks so the predictions are sometimes wrong
+    fn scripted_input(player: usize, tick: u32) -> u8 {
+        ((tick / (5 + player as u32 * 2) + player as u32) % 4) as u8
+    }
+
+    //One side of the match, it plays like VersusState::advance_tick
+    struct Peer {
+        session: NetSession,
+        rollback: Rollback<(ToyMatch, GameRngState)>,
+        state: ToyMatch,
+        //Both peers share the thread, each one keeps its own game rng
+        rng: GameRngState,
+        rollbacks: u32,
+    }
+
+    impl Peer {
+        fn new(player_index: usize, rng: GameRngState) -> Peer {
+            let mut session = create_net_session("127.0.0.1:0", "127.0.0.1:9", WINDOW_SIZE).unwrap();
+            session.player_index = player_index;
+
+            Peer {
+                session,
+                rollback: create_rollback(),
+                state: ToyMatch::default(),
+                rng,
+                rollbacks: 0,
+            }
+        }
+
+        fn play_tick(&mut self) {
+            let inputs = self.rollback.begin_tick(&self.session, (self.state.clone(), game_rng_state()));
+            toy_step(&mut self.state, inputs);
+            self.rollback.end_tick();
+        }
+
+        fn advance(&mut self) {
+            restore_game_rng(self.rng);
+
+            if let Some(tick_state) = self.rollback.take_mispredicted(&self.session) {
+                let end_tick = self.rollback.tick;
+                let (state, rng) = tick_state.state;
+
+                self.state = state;
+                restore_game_rng(rng);
+                self.rollback.tick = tick_state.tick;
+                self.rollbacks += 1;
+
+                while self.rollback.tick < end_tick {
+                    self.play_tick();
+                }
+            }
+
+            self.rollback.forget_confirmed(&self.session);
+
+            if self.rollback.tick < MATCH_TICKS && !self.rollback.is_waiting(&self.session) {
+                let input = scripted_input(self.session.player_index, self.rollback.tick);
+                self.session.push_local_input(self.rollback.tick, input);
+                self.play_tick();
+            }
+
+            self.rng = game_rng_state();
+        }
+    }
+
+    //Packet on its way, delivered at frame
+    struct InFlight {
+        frame: u32,
+        to: usize,
+        message: NetMessage,
+    }
+
+    //In-memory network losing, delaying and reordering the packets
+    fn play_match(network: &mut StdRng, max_delay: u32, loss: f64) -> [Peer; 2] {
+        seed_game_rng(42);
+        let rng = game_rng_state();
+        let mut peers = [Peer::new(0, rng), Peer::new(1, rng)];
+        let mut in_flight: Vec<InFlight> = Vec::new();
+        let mut frame = 0;
+
+        while peers.iter().any(|peer| peer.rollback.tick < MATCH_TICKS || !peer.rollback.history.is_empty()) {
+            assert!(frame < MATCH_TICKS * 20, "the match never ends");
+
+            for (index, peer) in peers.iter_mut().enumerate() {
+                peer.advance();
+
+                if !network.gen_bool(loss) {
+                    let delay = network.gen_range(0..=max_delay);
+                    in_flight.push(InFlight{ frame: frame + delay, to: 1 - index, message: peer.session.inputs_message() });
+                }
+            }
+
+            //Packets arriving on the same frame come in any order
+            let (mut arrived, waiting): (Vec<InFlight>, Vec<InFlight>) = in_flight.into_iter().partition(|packet| packet.frame <= frame);
+            in_flight = waiting;
+
+            while !arrived.is_empty() {
+                let packet = arrived.swap_remove(network.gen_range(0..arrived.len()));
+                peers[packet.to].session.handle_message(packet.message);
+            }
+
+            frame += 1;
+        }
+
+        peers
+    }
+
+    //The match played straight from the inputs both sides ended up with
+    fn reference_match(peers: &[Peer; 2]) -> ToyMatch {
+        seed_game_rng(42);
+        let mut state = ToyMatch::default();
+
+        for tick in 0..MATCH_TICKS as usize {
+            toy_step(&mut state, [peers[0].session.local_inputs[tick], peers[1].session.local_inputs[tick]]);
+        }
+
+        state
+    }
+
+    #[test]
+    fn both_sides_end_in_the_same_state_with_delayed_and_reordered_inputs() {
+        let mut network = StdRng::seed_from_u64(7);
+        let peers = play_match(&mut network, 6, 0.2);
+
+        assert!(peers.iter().all(|peer| peer.rollbacks > 0), "no prediction was ever wrong");
+        assert_eq!(peers[0].state, peers[1].state);
+        assert_eq!(peers[0].state.checksum, reference_match(&peers).checksum);
+        assert_eq!(peers[0].state, reference_match(&peers));
+    }
+
+    #[test]
+    fn both_sides_agree_on_a_perfect_network() {
+        let mut network = StdRng::seed_from_u64(1);
+        let peers = play_match(&mut network, 0, 0.0);
+
+        assert_eq!(peers[0].state, peers[1].state);
+        assert_eq!(peers[0].state, reference_match(&peers));
+    }
+
+    #[test]
+    fn remote_inputs_are_only_taken_without_gaps() {
+        let mut session = Peer::new(0, GameRngState::default()).session;
+
+        session.handle_message(NetMessage::Inputs{ first_tick: 2, inputs: vec![3, 3], ack: 0 });
+        assert!(session.remote_inputs.is_empty());
+
+        session.handle_message(NetMessage::Inputs{ first_tick: 0, inputs: vec![1, 2, 3], ack: 1 });
+        session.handle_message(NetMessage::Inputs{ first_tick: 1, inputs: vec![9, 9, 4], ack: 0 });
+        assert_eq!(session.remote_inputs, vec![1, 2, 3, 4]);
+        assert_eq!(session.remote_ack, 1);
+        assert_eq!(session.remote_input(10), 4);
+    }
+
+    #[test]
+    fn rollback_waits_for_the_opponent() {
+        let mut peer = Peer::new(0, GameRngState::default());
+
+        for _ in 0..MAX_ROLLBACK * 2 {
+            peer.advance();
+        }
+
+        assert_eq!(peer.rollback.tick, MAX_ROLLBACK);
+        assert!(peer.rollback.is_waiting(&peer.session));
+    }
+
+    fn receive_until(session: &mut NetSession, done: impl Fn(&NetSession) -> bool) {
+        for _ in 0..200 {
+            session.receive();
+
+            if done(session) {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn hellos_are_exchanged_once_the_other_side_listens() {
+        //Nothing listens there yet, sending is refused
+        let remote_address = UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let mut first = create_net_session("127.0.0.1:0", &remote_address.to_string(), WINDOW_SIZE).unwrap();
+        first.send_hello();
+        first.receive();
+        first.send_hello();
+        first.receive();
+
+        assert!(first.error.is_none());
+        assert!(first.match_seed.is_none());
+
+        let first_address = first.socket.local_addr().unwrap().to_string();
+        let mut second = create_net_session(&remote_address.to_string(), &first_address, WINDOW_SIZE).unwrap();
+        assert_ne!(first.player_index, second.player_index);
+
+        second.send_hello();
+        receive_until(&mut first, |session| session.match_seed.is_some());
+        first.send_hello();
+        receive_until(&mut second, |session| session.match_seed.is_some());
+
+        assert!(first.error.is_none() && second.error.is_none());
+        assert!(first.match_seed.is_some());
+        assert_eq!(first.match_seed, second.match_seed);
+    }
+
+    #[test]
+    fn hello_of_another_version_is_refused() {
+        let mut session = Peer::new(0, GameRngState::default()).session;
+        session.handle_message(NetMessage::Hello{ version: NETPLAY_VERSION + 1, seed: 1, window_size: WINDOW_SIZE });
+
+        assert!(session.error.is_some());
+        assert!(session.match_seed.is_none());
+    }
+}
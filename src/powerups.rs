@@ -0,0 +1,195 @@
+use ggez::glam::*;
+use ggez::graphics::Color;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::actor::{Actor, ActorType};
+use crate::rng::game_rng;
+use crate::weapon::{create_piercing_laser, create_spread_gun, Weapon};
+
+pub const POWERUP_DROP_CHANCE : f64 = 0.1;
+pub const POWERUP_SPEED : f32 = 150.0;
+pub const POWERUP_DURATION : f32 = 8.0;
+pub const POWERUP_SIZE : Vec2 = Vec2{ x: 22.0, y: 12.0 };
+pub const RAPID_FIRE_SHOT_TIME : f32 = 0.2;
+pub const SHIELD_REGEN_TIME : f32 = 1.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerupType {
+    RapidFire,
+    TripleShot,
+    PiercingLaser,
+    ShieldRegen,
+    ExtraLife,
+}
+
+pub const POWERUP_TYPES : [PowerupType; 5] = [
+    PowerupType::RapidFire,
+    PowerupType::TripleShot,
+    PowerupType::PiercingLaser,
+    PowerupType::ShieldRegen,
+    PowerupType::ExtraLife,
+];
+
+impl PowerupType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerupType::RapidFire => "Rapid fire",
+            PowerupType::TripleShot => "Triple shot",
+            PowerupType::PiercingLaser => "Laser",
+            PowerupType::ShieldRegen => "Shield regen",
+            PowerupType::ExtraLife => "Extra life",
+        }
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            PowerupType::RapidFire => "R",
+            PowerupType::TripleShot => "T",
+            PowerupType::PiercingLaser => "P",
+            PowerupType::ShieldRegen => "S",
+            PowerupType::ExtraLife => "L",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerupType::RapidFire => Color::from_rgb(230, 60, 60),
+            PowerupType::TripleShot => Color::from_rgb(60, 140, 230),
+            PowerupType::PiercingLaser => Color::from_rgb(200, 60, 220),
+            PowerupType::ShieldRegen => Color::from_rgb(60, 200, 90),
+            PowerupType::ExtraLife => Color::from_rgb(230, 200, 50),
+        }
+    }
+}
+
+//Remaining time of every timed powerup, 0.0 means the powerup is not active
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ActivePowerups {
+    pub rapid_fire: f32,
+    pub triple_shot: f32,
+    pub piercing_laser: f32,
+    pub shield_regen: f32,
+    pub time_to_regen: f32,
+}
+
+impl ActivePowerups {
+    //Starts or refreshes a timed powerup. Extra life is instant and is handled by the game state
+    pub fn activate(&mut self, powerup_type: PowerupType) {
+        match powerup_type {
+            PowerupType::RapidFire => self.rapid_fire = POWERUP_DURATION,
+            PowerupType::TripleShot => self.triple_shot = POWERUP_DURATION,
+            PowerupType::PiercingLaser => self.piercing_laser = POWERUP_DURATION,
+            PowerupType::ShieldRegen => {
+                self.shield_regen = POWERUP_DURATION;
+                self.time_to_regen = SHIELD_REGEN_TIME;
+            },
+            PowerupType::ExtraLife => ()
+        }
+    }
+
+    pub fn is_active(&self, powerup_type: PowerupType) -> bool {
+        self.remaining_time(powerup_type) > 0.0
+    }
+
+    pub fn remaining_time(&self, powerup_type: PowerupType) -> f32 {
+        match powerup_type {
+            PowerupType::RapidFire => self.rapid_fire,
+            PowerupType::TripleShot => self.triple_shot,
+            PowerupType::PiercingLaser => self.piercing_laser,
+            PowerupType::ShieldRegen => self.shield_regen,
+            PowerupType::ExtraLife => 0.0,
+        }
+    }
+
+    //Counts down the timers, returns true when the shields should regenerate one hp
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.rapid_fire = (self.rapid_fire - delta_time).max(0.0);
+        self.triple_shot = (self.triple_shot - delta_time).max(0.0);
+        self.piercing_laser = (self.piercing_laser - delta_time).max(0.0);
+
+        if self.shield_regen <= 0.0 {
+            return false;
+        }
+
+        self.shield_regen = (self.shield_regen - delta_time).max(0.0);
+        self.time_to_regen -= delta_time;
+
+        if self.time_to_regen <= 0.0 {
+            self.time_to_regen = SHIELD_REGEN_TIME;
+            return true;
+        }
+
+        false
+    }
+
+    //Weapon the player is actually using with the active powerups applied on top of the equipped one
+    pub fn modify_weapon(&self, weapon: &Weapon) -> Weapon {
+        let mut weapon = *weapon;
+
+        if self.is_active(PowerupType::RapidFire) {
+            weapon.fire_time = weapon.fire_time.min(RAPID_FIRE_SHOT_TIME);
+        }
+
+        //The powerups lend the shot of their weapon, the equipped one keeps its fire rate and bullet limit
+        if self.is_active(PowerupType::TripleShot) {
+            let spread_gun = create_spread_gun();
+            weapon.projectiles = weapon.projectiles.max(spread_gun.projectiles);
+            weapon.spread = weapon.spread.max(spread_gun.spread);
+        }
+
+        if self.is_active(PowerupType::PiercingLaser) {
+            let laser = create_piercing_laser();
+            weapon.piercing = laser.piercing;
+            weapon.damage = weapon.damage.max(laser.damage);
+        }
+
+        weapon
+    }
+
+    pub fn timers_text(&self) -> String {
+        let mut timers: Vec<String> = Vec::new();
+
+        for powerup_type in POWERUP_TYPES {
+            if self.is_active(powerup_type) {
+                timers.push(format!("{}: {:.1}", powerup_type.name(), self.remaining_time(powerup_type)));
+            }
+        }
+
+        timers.join("\n")
+    }
+}
+
+pub fn create_powerup(powerup_type: PowerupType, position: Vec2) -> Actor {
+    Actor {
+        tag: ActorType::Powerup(powerup_type),
+        position,
+        direction: Vec2{ x: 0.0, y: 1.0 },
+        size: POWERUP_SIZE,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: 1.0,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+    }
+}
+
+//Rolls the drop chance for a destroyed invader
+pub fn roll_powerup_drop(position: Vec2) -> Option<Actor> {
+    let mut rng = game_rng();
+
+    if !rng.gen_bool(POWERUP_DROP_CHANCE) {
+        return None;
+    }
+
+    Some(create_random_powerup(position))
+}
+
+pub fn create_random_powerup(position: Vec2) -> Actor {
+    let powerup_type = POWERUP_TYPES[game_rng().gen_range(0..POWERUP_TYPES.len())];
+    create_powerup(powerup_type, position)
+}
+
+pub fn update_powerup_position(actor: &mut Actor, dt: f32) {
+    actor.position += actor.direction * (POWERUP_SPEED * dt);
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use ggez::glam::*;
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::game::GameState;
+use crate::input::InputState;
+use crate::ruleset::{Difficulty, Ruleset};
+use crate::scoring::ScoringMode;
+use crate::stats::{read_user_file, write_user_file};
+
+//Bumped every time the simulation changes in a way that breaks older replays
+pub const REPLAY_VERSION : u32 = 2;
+//Best run played on this machine, shown by the attract mode
+pub const DEMO_REPLAY_FILE : &str = "/demo_replay.json";
+
+//A whole run from its start to the game over, the game rng makes it reproducible from the inputs
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub ruleset: Ruleset,
+    //Older replays were all played on normal
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub scoring_mode: ScoringMode,
+    pub simulation_rate: u32,
+    pub window_size: Vec2,
+    pub seed: u64,
+    //Runs of (input bits, steps), the input rarely changes between two steps
+    pub inputs: Vec<(u8, u32)>,
+    pub score: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    //Set by anything the replay can't reproduce, like the console or a rewind
+    pub tainted: bool,
+}
+
+pub fn create_replay_recorder(ruleset: Ruleset, difficulty: Difficulty, scoring_mode: ScoringMode, simulation_rate: u32, window_size: Vec2, seed: u64) -> ReplayRecorder {
+    ReplayRecorder {
+        replay: Replay {
+            version: REPLAY_VERSION,
+            ruleset,
+            difficulty,
+            scoring_mode,
+            simulation_rate,
+            window_size,
+            seed,
+            inputs: Vec::new(),
+            score: 0,
+        },
+        tainted: false,
+    }
+}
+
+impl Replay {
+    pub fn steps(&self) -> u32 {
+        self.inputs.iter().map(|(_, steps)| steps).sum()
+    }
+}
+
+impl ReplayRecorder {
+    pub fn is_empty(&self) -> bool {
+        self.replay.inputs.is_empty()
+    }
+
+    pub fn record(&mut self, input: &InputState) {
+        let bits = input.to_bits();
+
+        match self.replay.inputs.last_mut() {
+            Some((last_bits, steps)) if *last_bits == bits => *steps += 1,
+            _ => self.replay.inputs.push((bits, 1))
+        }
+    }
+
+    //Ends the run, a tainted run gives no replay
+    pub fn finish(&mut self, score: u32) -> Option<Replay> {
+        if self.tainted {
+            return None;
+        }
+
+        let mut replay = std::mem::take(&mut self.replay);
+        replay.score = score;
+        Some(replay)
+    }
+}
+
+//Iterates over the inputs of a replay one step at a time
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub run: usize,
+    pub step_in_run: u32,
+}
+
+pub fn create_replay_player(replay: Replay) -> ReplayPlayer {
+    ReplayPlayer {
+        replay,
+        run: 0,
+        step_in_run: 0,
+    }
+}
+
+impl ReplayPlayer {
+    //None once every step was played
+    pub fn next_input(&mut self) -> Option<InputState> {
+        let (bits, steps) = *self.replay.inputs.get(self.run)?;
+
+        self.step_in_run += 1;
+
+        if self.step_in_run >= steps {
+            self.run += 1;
+            self.step_in_run = 0;
+        }
+
+        Some(InputState::from_bits(bits))
+    }
+}
+
+//Puts a headless game at the start of the run recorded in the replay
+pub fn prepare_replay_game(game: &mut GameState, replay: &Replay) -> Result<(), String> {
+    if replay.version != REPLAY_VERSION {
+        return Err(format!("replay version {} is not supported", replay.version));
+    }
+
+    if game.window.size != replay.window_size {
+        return Err(format!("replay was made for a {}x{} window", replay.window_size.x, replay.window_size.y));
+    }
+
+    game.set_ruleset(replay.ruleset);
+    game.set_difficulty(replay.difficulty);
+    game.set_scoring_mode(replay.scoring_mode);
+    game.set_simulation_rate(replay.simulation_rate);
+    game.events.events.clear();
+    game.finished_replay = None;
+    game.start_replay_with_seed(replay.seed);
+
+    Ok(())
+}
+
+//Plays the replay without a window or sound, returns the score of the run
+pub fn simulate_replay(ctx: &mut Context, replay: &Replay) -> Result<u32, String> {
+    let mut game = GameState::new_headless(ctx).map_err(|error| error.to_string())?;
+    prepare_replay_game(&mut game, replay)?;
+
+    let steps = replay.steps();
+    let mut step = 0;
+
+    for (bits, count) in &replay.inputs {
+        game.input = InputState::from_bits(*bits);
+
+        for _ in 0..*count {
+            step += 1;
+            game.step(ctx, game.timestep.step_time).map_err(|error| error.to_string())?;
+
+            if game.finished_replay.is_some() && step < steps {
+                return Err(format!("game over at step {} of {}", step, steps));
+            }
+        }
+    }
+
+    match game.finished_replay {
+        Some(finished) => Ok(finished.score),
+        None => Err(String::from("replay ends before the game over"))
+    }
+}
+
+pub fn load_demo_replay(ctx: &Context) -> Option<Replay> {
+    read_user_file(ctx, DEMO_REPLAY_FILE).and_then(|text| serde_json::from_str(&text).ok())
+}
+
+//Only replaces the demo with a better run
+pub fn save_demo_replay(ctx: &Context, replay: &Replay) -> GameResult {
+    if load_demo_replay(ctx).is_some_and(|demo| demo.version == REPLAY_VERSION && demo.score > replay.score) {
+        return Ok(());
+    }
+
+    let text = serde_json::to_string(replay).map_err(|error| ggez::GameError::CustomError(error.to_string()))?;
+    write_user_file(ctx, DEMO_REPLAY_FILE, &text)
+}
+
+pub fn load_replay_file(path: &Path) -> Result<Replay, String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+    serde_json::from_str(&text).map_err(|error| format!("can't parse {}: {}", path.display(), error))
+}
+
+pub fn save_replay_file(path: &Path, replay: &Replay) -> Result<(), String> {
+    let text = serde_json::to_string(replay).map_err(|error| error.to_string())?;
+    std::fs::write(path, text).map_err(|error| format!("can't write {}: {}", path.display(), error))
+}
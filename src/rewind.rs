@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use ggez::input::keyboard::KeyCode;
+
+use crate::snapshot::Snapshot;
+
+//Seconds of simulation kept while the debug overlay is enabled
+pub const REWIND_SECONDS : f32 = 10.0;
+
+pub const REWIND_BACK_KEY : KeyCode = KeyCode::F7;
+pub const REWIND_FORWARD_KEY : KeyCode = KeyCode::F8;
+
+//Ring buffer of the state at the start of the last ticks
+#[derive(Debug, Default)]
+pub struct RewindBuffer {
+    pub snapshots: VecDeque<Snapshot>,
+    //Index of the snapshot being looked at, None while the game runs at the newest tick
+    pub cursor: Option<usize>,
+}
+
+impl RewindBuffer {
+    pub fn record(&mut self, snapshot: Snapshot, simulation_rate: u32) {
+        let capacity = ((REWIND_SECONDS * simulation_rate as f32) as usize).max(1);
+
+        self.snapshots.push_back(snapshot);
+
+        while self.snapshots.len() > capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn step_back(&mut self) -> Option<&Snapshot> {
+        let cursor = match self.cursor {
+            Some(cursor) => cursor.checked_sub(1)?,
+            None => self.snapshots.len().checked_sub(1)?
+        };
+
+        self.cursor = Some(cursor);
+        self.snapshots.get(cursor)
+    }
+
+    pub fn step_forward(&mut self) -> Option<&Snapshot> {
+        let cursor = self.cursor? + 1;
+
+        if cursor >= self.snapshots.len() {
+            return None;
+        }
+
+        self.cursor = Some(cursor);
+        self.snapshots.get(cursor)
+    }
+
+    //The game continues from the snapshot under the cursor, the ticks after it are forgotten
+    pub fn resume(&mut self) {
+        if let Some(cursor) = self.cursor.take() {
+            self.snapshots.truncate(cursor);
+        }
+    }
+
+    //How many ticks back from the newest one the cursor is
+    pub fn ticks_back(&self) -> usize {
+        match self.cursor {
+            Some(cursor) => self.snapshots.len() - cursor,
+            None => 0
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.cursor = None;
+    }
+}
@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+thread_local! {
+    static GAME_RNG: RefCell<ChaCha12Rng> = RefCell::new(ChaCha12Rng::from_entropy());
+}
+
+//Whole state of the game rng, a snapshot keeps it without disturbing the numbers drawn next
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+//Handle to the generator used by the simulation, works like rand::thread_rng but can be seeded
+#[derive(Debug, Copy, Clone)]
+pub struct GameRng;
+
+pub fn game_rng() -> GameRng {
+    GameRng
+}
+
+//Reseeds the simulation so a run can be reproduced
+pub fn seed_game_rng(seed: u64) {
+    GAME_RNG.with(|rng| *rng.borrow_mut() = ChaCha12Rng::seed_from_u64(seed));
+}
+
+pub fn game_rng_state() -> GameRngState {
+    GAME_RNG.with(|rng| {
+        let rng = rng.borrow();
+
+        GameRngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+    })
+}
+
+pub fn restore_game_rng(state: GameRngState) {
+    let mut restored = ChaCha12Rng::from_seed(state.seed);
+    restored.set_stream(state.stream);
+    restored.set_word_pos(state.word_pos);
+
+    GAME_RNG.with(|rng| *rng.borrow_mut() = restored);
+}
+
+//Restarts the generator from a seed drawn from itself, so its whole state fits in the returned u64
+pub fn reseed_game_rng() -> u64 {
+    let seed = game_rng().next_u64();
+    seed_game_rng(seed);
+    seed
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        GAME_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        GAME_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        GAME_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        GAME_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
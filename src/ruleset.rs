@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::actor::ActorType;
+use crate::collision::CollisionRules;
+use crate::enemies::EnemyDefinitions;
+use crate::weapon::{create_arcade_cannon, create_blaster, Weapon};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Ruleset {
+    //Powerups, bosses, diving enemies and the per kill speed up
+    #[default]
+    Modern,
+    //Rules of the 1978 arcade game
+    Classic,
+}
+
+pub const RULESETS : [Ruleset; 2] = [Ruleset::Modern, Ruleset::Classic];
+
+impl Ruleset {
+    pub fn weapon(&self) -> Weapon {
+        match self {
+            Ruleset::Modern => create_blaster(),
+            Ruleset::Classic => create_arcade_cannon(),
+        }
+    }
+
+    pub fn enemy_definitions(&self) -> EnemyDefinitions {
+        match self {
+            Ruleset::Modern => EnemyDefinitions::default(),
+            Ruleset::Classic => EnemyDefinitions::classic(),
+        }
+    }
+
+    pub fn collision_rules(&self) -> CollisionRules {
+        match self {
+            Ruleset::Modern => CollisionRules::default(),
+            //All classic shots move at the same speed, the plunger is the one player shots can't stop and has to be dodged
+            Ruleset::Classic => CollisionRules {
+                clash_survivors: &[ActorType::EnemyBulletPlunger],
+                ..CollisionRules::default()
+            },
+        }
+    }
+
+    pub fn powerups_enabled(&self) -> bool {
+        *self == Ruleset::Modern
+    }
+
+    pub fn bosses_enabled(&self) -> bool {
+        *self == Ruleset::Modern
+    }
+
+    pub fn ufo_enabled(&self) -> bool {
+        *self == Ruleset::Classic
+    }
+}
+
+//Speeds up or slows down the enemy fire, part of the replays like the ruleset
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+pub const DIFFICULTIES : [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+
+    //Scales the time the enemies wait between two shots
+    pub fn enemy_fire_rate(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use serde::{Deserialize, Serialize};
+
+use crate::actor::world_to_screen_coords;
+use crate::assets::Assets;
+use crate::font::BitmapFont;
+
+//Multiplier gained by every hit of a combo
+pub const COMBO_MULTIPLIER_STEP : f32 = 0.1;
+pub const MAX_MULTIPLIER : f32 = 4.0;
+//Time without hits before the multiplier starts to decay
+pub const MULTIPLIER_GRACE_TIME : f32 = 1.5;
+//Multiplier lost every second after the grace time
+pub const MULTIPLIER_DECAY : f32 = 0.5;
+pub const ROW_CLEAR_BONUS : u32 = 500;
+pub const COLUMN_CLEAR_BONUS : u32 = 300;
+//Awarded at the end of a wave scaled by the accuracy of the wave
+pub const ACCURACY_BONUS : u32 = 1000;
+pub const POPUP_TIME : f32 = 0.8;
+pub const POPUP_SPEED : f32 = 40.0;
+pub const POPUP_TEXT_SIZE : f32 = 10.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScoringMode {
+    //Fixed score per enemy type
+    #[default]
+    Flat,
+    //Combo multiplier, clear bonuses and score popups
+    Combo,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScorePopup {
+    pub position: Vec2,
+    pub text: String,
+    pub time_left: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scoring {
+    pub mode: ScoringMode,
+    //Consecutive hits without a miss
+    pub combo: u32,
+    pub multiplier: f32,
+    pub time_from_last_hit: f32,
+    pub wave_shots: u32,
+    pub wave_hits: u32,
+    #[serde(skip)]
+    pub popups: Vec<ScorePopup>,
+}
+
+pub fn create_scoring(mode: ScoringMode) -> Scoring {
+    Scoring {
+        mode,
+        combo: 0,
+        multiplier: 1.0,
+        time_from_last_hit: 0.0,
+        wave_shots: 0,
+        wave_hits: 0,
+        popups: Vec::new(),
+    }
+}
+
+impl Scoring {
+    pub fn register_shot(&mut self) {
+        self.wave_shots += 1;
+    }
+
+    //Every hit feeds the combo, only the first hit of a bullet counts for the accuracy
+    pub fn register_hit(&mut self, first_hit: bool) {
+        if first_hit {
+            self.wave_hits += 1;
+        }
+
+        self.combo += 1;
+        self.multiplier = (self.multiplier + COMBO_MULTIPLIER_STEP).min(MAX_MULTIPLIER);
+        self.time_from_last_hit = 0.0;
+    }
+
+    //A bullet left the screen or hit a shield
+    pub fn register_miss(&mut self) {
+        self.combo = 0;
+        self.multiplier = 1.0;
+    }
+
+    //Score of a kill worth points, multiplied and shown at position in combo mode
+    pub fn award(&mut self, points: u32, position: Vec2) -> u32 {
+        if self.mode == ScoringMode::Flat {
+            return points;
+        }
+
+        let points = (points as f32 * self.multiplier).round() as u32;
+        self.add_popup(position, format!("{}", points));
+        points
+    }
+
+    //Bonuses are only given in combo mode
+    pub fn bonus(&mut self, points: u32, position: Vec2, label: &str) -> u32 {
+        if self.mode == ScoringMode::Flat || points == 0 {
+            return 0;
+        }
+
+        self.add_popup(position, format!("{} +{}", label, points));
+        points
+    }
+
+    //Accuracy bonus of the cleared wave, also starts counting the next wave
+    pub fn wave_bonus(&mut self, position: Vec2) -> u32 {
+        let accuracy = if self.wave_shots > 0 { (self.wave_hits as f32 / self.wave_shots as f32).min(1.0) } else { 0.0 };
+        self.wave_shots = 0;
+        self.wave_hits = 0;
+
+        self.bonus((ACCURACY_BONUS as f32 * accuracy).round() as u32, position, "ACCURACY")
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time_from_last_hit += delta_time;
+
+        if self.time_from_last_hit > MULTIPLIER_GRACE_TIME {
+            self.multiplier = (self.multiplier - MULTIPLIER_DECAY * delta_time).max(1.0);
+
+            if self.multiplier <= 1.0 {
+                self.combo = 0;
+            }
+        }
+
+        for popup in &mut self.popups {
+            popup.position.y -= POPUP_SPEED * delta_time;
+            popup.time_left -= delta_time;
+        }
+
+        self.popups.retain(|popup| popup.time_left > 0.0);
+    }
+
+    pub fn reset(&mut self) {
+        *self = create_scoring(self.mode);
+    }
+
+    pub fn combo_text(&self) -> String {
+        if self.mode == ScoringMode::Flat || self.combo == 0 {
+            return String::new();
+        }
+
+        format!("COMBO {} X{:.1}", self.combo, self.multiplier)
+    }
+
+    fn add_popup(&mut self, position: Vec2, text: String) {
+        self.popups.push(ScorePopup { position, text, time_left: POPUP_TIME });
+    }
+}
+
+pub fn draw_score_popups(canvas: &mut graphics::Canvas, assets: &Assets, scoring: &Scoring, window_size: Vec2) {
+    canvas.set_sampler(graphics::Sampler::nearest_clamp());
+
+    for popup in &scoring.popups {
+        let center = world_to_screen_coords(window_size.x, window_size.y, popup.position);
+        let dimensions = BitmapFont::text_dimensions(&popup.text, POPUP_TEXT_SIZE);
+        let alpha = (popup.time_left / POPUP_TIME).clamp(0.0, 1.0);
+
+        assets.font.draw(canvas, &popup.text, center - dimensions / 2.0, POPUP_TEXT_SIZE, Color::new(1.0, 1.0, 0.4, alpha));
+    }
+}
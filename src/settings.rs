@@ -0,0 +1,364 @@
+use std::io::Read;
+use std::path::Path;
+
+use ggez::conf::{FullscreenType, WindowMode};
+use ggez::filesystem::Filesystem;
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::assets::Assets;
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::input::{key_name, KeyBindings, BINDABLE_KEYS};
+use crate::ruleset::{Difficulty, Ruleset, DIFFICULTIES, RULESETS};
+use crate::stats::write_user_file;
+
+pub const SETTINGS_FILE : &str = "/settings.json";
+//The playfield is scaled to the window, with black bars when the aspect ratio differs
+pub const RESOLUTIONS : [(f32, f32); 5] = [(650.0, 700.0), (975.0, 1050.0), (1300.0, 1400.0), (1280.0, 720.0), (1920.0, 1080.0)];
+pub const VOLUME_STEP : f32 = 0.1;
+pub const SETTINGS_TEXT_SIZE : f32 = 14.0;
+pub const SETTINGS_TITLE_SIZE : f32 = 32.0;
+
+//Tint of the whole screen, like the phosphor of old monochrome monitors
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorFilter {
+    #[default]
+    Off,
+    Green,
+    Amber,
+}
+
+pub const COLOR_FILTERS : [ColorFilter; 3] = [ColorFilter::Off, ColorFilter::Green, ColorFilter::Amber];
+
+impl ColorFilter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorFilter::Off => "OFF",
+            ColorFilter::Green => "GREEN",
+            ColorFilter::Amber => "AMBER",
+        }
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            ColorFilter::Off => None,
+            ColorFilter::Green => Some(Color::new(0.45, 1.0, 0.45, 1.0)),
+            ColorFilter::Amber => Some(Color::new(1.0, 0.75, 0.3, 1.0)),
+        }
+    }
+}
+
+//Options of the settings menu, saved in the user directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub resolution: (f32, f32),
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub master_volume: f32,
+    pub effects_volume: f32,
+    pub key_bindings: KeyBindings,
+    pub difficulty: Difficulty,
+    pub ruleset: Ruleset,
+    pub color_filter: ColorFilter,
+    //Red and green strips like the arcade cabinet
+    pub cellophane: bool,
+    pub crt: bool,
+    pub show_fps: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            resolution: RESOLUTIONS[0],
+            fullscreen: false,
+            vsync: true,
+            master_volume: 1.0,
+            effects_volume: 1.0,
+            key_bindings: KeyBindings::default(),
+            difficulty: Difficulty::default(),
+            ruleset: Ruleset::default(),
+            color_filter: ColorFilter::default(),
+            cellophane: false,
+            crt: false,
+            show_fps: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn window_mode(&self) -> WindowMode {
+        let fullscreen_type = if self.fullscreen { FullscreenType::Desktop } else { FullscreenType::Windowed };
+
+        WindowMode::default()
+            .dimensions(self.resolution.0, self.resolution.1)
+            .fullscreen_type(fullscreen_type)
+    }
+
+    pub fn sound_volume(&self) -> f32 {
+        self.master_volume * self.effects_volume
+    }
+}
+
+//Missing or broken settings fall back to the defaults
+pub fn load_settings(fs: &Filesystem) -> Settings {
+    if !fs.is_file(SETTINGS_FILE) {
+        return Settings::default();
+    }
+
+    let mut text = String::new();
+
+    match fs.open(SETTINGS_FILE).map(|mut file| file.read_to_string(&mut text)) {
+        Ok(Ok(_)) => serde_json::from_str(&text).unwrap_or_default(),
+        _ => Settings::default()
+    }
+}
+
+//The window is created from the settings, so they are read before the Context exists
+pub fn load_settings_before_build(game_id: &str, author: &str) -> Settings {
+    match Filesystem::new(game_id, author, "resources", "resources.zip") {
+        Ok(fs) => load_settings(&fs),
+        Err(_) => Settings::default()
+    }
+}
+
+//A settings file given on the command line, unlike the user one it has to be valid
+pub fn load_settings_from_path(path: &Path) -> Result<Settings, String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+    serde_json::from_str(&text).map_err(|error| format!("can't parse {}: {}", path.display(), error))
+}
+
+pub fn save_settings(ctx: &Context, settings: &Settings) -> GameResult {
+    let text = serde_json::to_string_pretty(settings).map_err(|error| ggez::GameError::CustomError(error.to_string()))?;
+    write_user_file(ctx, SETTINGS_FILE, &text)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SettingsEntry {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    MasterVolume,
+    EffectsVolume,
+    LeftKey,
+    RightKey,
+    FireKey,
+    Difficulty,
+    Ruleset,
+    ColorFilter,
+    Cellophane,
+    Crt,
+    ShowFps,
+    Back,
+}
+
+pub const SETTINGS_ENTRIES : [SettingsEntry; 15] = [
+    SettingsEntry::Resolution,
+    SettingsEntry::Fullscreen,
+    SettingsEntry::Vsync,
+    SettingsEntry::MasterVolume,
+    SettingsEntry::EffectsVolume,
+    SettingsEntry::LeftKey,
+    SettingsEntry::RightKey,
+    SettingsEntry::FireKey,
+    SettingsEntry::Difficulty,
+    SettingsEntry::Ruleset,
+    SettingsEntry::ColorFilter,
+    SettingsEntry::Cellophane,
+    SettingsEntry::Crt,
+    SettingsEntry::ShowFps,
+    SettingsEntry::Back,
+];
+
+impl SettingsEntry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SettingsEntry::Resolution => "RESOLUTION",
+            SettingsEntry::Fullscreen => "FULLSCREEN",
+            //Only read when the window is created
+            SettingsEntry::Vsync => "VSYNC *",
+            SettingsEntry::MasterVolume => "MASTER VOLUME",
+            SettingsEntry::EffectsVolume => "EFFECTS VOLUME",
+            SettingsEntry::LeftKey => "MOVE LEFT",
+            SettingsEntry::RightKey => "MOVE RIGHT",
+            SettingsEntry::FireKey => "FIRE",
+            SettingsEntry::Difficulty => "DIFFICULTY",
+            SettingsEntry::Ruleset => "RULESET",
+            SettingsEntry::ColorFilter => "COLOUR FILTER",
+            SettingsEntry::Cellophane => "CELLOPHANE",
+            SettingsEntry::Crt => "CRT SHADER",
+            SettingsEntry::ShowFps => "SHOW FPS",
+            SettingsEntry::Back => "BACK",
+        }
+    }
+}
+
+//Opened from the title screen, the ruleset and difficulty can't change during a run
+#[derive(Debug, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    pub selected: usize,
+    //The next key pressed is bound to the selected control
+    pub waiting_for_key: bool,
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "ON" } else { "OFF" }
+}
+
+fn setting_text(settings: &Settings, entry: SettingsEntry) -> String {
+    match entry {
+        SettingsEntry::Resolution => format!("{}X{}", settings.resolution.0, settings.resolution.1),
+        SettingsEntry::Fullscreen => on_off(settings.fullscreen).to_string(),
+        SettingsEntry::Vsync => on_off(settings.vsync).to_string(),
+        SettingsEntry::MasterVolume => format!("{:.0}%", settings.master_volume * 100.0),
+        SettingsEntry::EffectsVolume => format!("{:.0}%", settings.effects_volume * 100.0),
+        SettingsEntry::LeftKey => key_name(settings.key_bindings.left),
+        SettingsEntry::RightKey => key_name(settings.key_bindings.right),
+        SettingsEntry::FireKey => key_name(settings.key_bindings.fire),
+        SettingsEntry::Difficulty => settings.difficulty.name().to_string(),
+        SettingsEntry::Ruleset => format!("{:?}", settings.ruleset).to_uppercase(),
+        SettingsEntry::ColorFilter => settings.color_filter.name().to_string(),
+        SettingsEntry::Cellophane => on_off(settings.cellophane).to_string(),
+        SettingsEntry::Crt => on_off(settings.crt).to_string(),
+        SettingsEntry::ShowFps => on_off(settings.show_fps).to_string(),
+        SettingsEntry::Back => String::new(),
+    }
+}
+
+//Value next to the current one in the list, wraps around
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, direction: i32) -> T {
+    let index = values.iter().position(|value| *value == current).unwrap_or(0) as i32;
+    values[(index + direction).rem_euclid(values.len() as i32) as usize]
+}
+
+fn step_volume(volume: f32, direction: i32) -> f32 {
+    //Rounded so repeated steps don't drift away from the tenths
+    ((volume + VOLUME_STEP * direction as f32).clamp(0.0, 1.0) * 10.0).round() / 10.0
+}
+
+fn change_setting(ctx: &mut Context, game: &mut GameState, entry: SettingsEntry, direction: i32) -> GameResult {
+    let settings = &mut game.settings;
+
+    match entry {
+        SettingsEntry::Resolution => {
+            settings.resolution = cycle(&RESOLUTIONS, settings.resolution, direction);
+            ctx.gfx.set_mode(settings.window_mode())?;
+        },
+        SettingsEntry::Fullscreen => {
+            settings.fullscreen = !settings.fullscreen;
+            ctx.gfx.set_mode(settings.window_mode())?;
+        },
+        SettingsEntry::Vsync => settings.vsync = !settings.vsync,
+        SettingsEntry::MasterVolume => settings.master_volume = step_volume(settings.master_volume, direction),
+        SettingsEntry::EffectsVolume => settings.effects_volume = step_volume(settings.effects_volume, direction),
+        SettingsEntry::Difficulty => settings.difficulty = cycle(&DIFFICULTIES, settings.difficulty, direction),
+        SettingsEntry::Ruleset => settings.ruleset = cycle(&RULESETS, settings.ruleset, direction),
+        SettingsEntry::ColorFilter => settings.color_filter = cycle(&COLOR_FILTERS, settings.color_filter, direction),
+        SettingsEntry::Cellophane => settings.cellophane = !settings.cellophane,
+        SettingsEntry::Crt => settings.crt = !settings.crt,
+        SettingsEntry::ShowFps => settings.show_fps = !settings.show_fps,
+        SettingsEntry::LeftKey | SettingsEntry::RightKey | SettingsEntry::FireKey | SettingsEntry::Back => return Ok(())
+    }
+
+    game.apply_settings();
+    save_settings(ctx, &game.settings)
+}
+
+fn bind_key(game: &mut GameState, entry: SettingsEntry, key: KeyCode) {
+    let bindings = &mut game.settings.key_bindings;
+
+    let previous = match entry {
+        SettingsEntry::LeftKey => bindings.left,
+        SettingsEntry::RightKey => bindings.right,
+        SettingsEntry::FireKey => bindings.fire,
+        _ => return
+    };
+
+    //A key already used by another control is swapped with it
+    for binding in [&mut bindings.left, &mut bindings.right, &mut bindings.fire] {
+        if *binding == key {
+            *binding = previous;
+        }
+    }
+
+    match entry {
+        SettingsEntry::LeftKey => bindings.left = key,
+        SettingsEntry::RightKey => bindings.right = key,
+        SettingsEntry::FireKey => bindings.fire = key,
+        _ => ()
+    }
+}
+
+pub fn handle_settings_key(ctx: &mut Context, game: &mut GameState, key: KeyCode) -> GameResult {
+    let entry = SETTINGS_ENTRIES[game.settings_menu.selected];
+
+    if game.settings_menu.waiting_for_key {
+        game.settings_menu.waiting_for_key = false;
+
+        if key == KeyCode::Escape || !BINDABLE_KEYS.contains(&key) {
+            return Ok(());
+        }
+
+        bind_key(game, entry, key);
+        return save_settings(ctx, &game.settings);
+    }
+
+    let menu = &mut game.settings_menu;
+
+    match key {
+        KeyCode::Up => menu.selected = (menu.selected + SETTINGS_ENTRIES.len() - 1) % SETTINGS_ENTRIES.len(),
+        KeyCode::Down => menu.selected = (menu.selected + 1) % SETTINGS_ENTRIES.len(),
+        KeyCode::Left => change_setting(ctx, game, entry, -1)?,
+        KeyCode::Right => change_setting(ctx, game, entry, 1)?,
+        KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => match entry {
+            SettingsEntry::Back => menu.open = false,
+            SettingsEntry::LeftKey | SettingsEntry::RightKey | SettingsEntry::FireKey => menu.waiting_for_key = true,
+            _ => change_setting(ctx, game, entry, 1)?
+        },
+        KeyCode::Escape => menu.open = false,
+        _ => ()
+    }
+
+    Ok(())
+}
+
+pub fn draw_settings_menu(canvas: &mut graphics::Canvas, assets: &Assets, menu: &SettingsMenu, settings: &Settings, window_size: Vec2) {
+    let title = "OPTIONS";
+    let title_dimensions = BitmapFont::text_dimensions(title, SETTINGS_TITLE_SIZE);
+    assets.font.draw(canvas, title, Vec2::new((window_size.x - title_dimensions.x) / 2.0, window_size.y / 8.0), SETTINGS_TITLE_SIZE, Color::WHITE);
+
+    let left = window_size.x * 0.12;
+    let right = window_size.x * 0.88;
+    let mut y = window_size.y / 8.0 + SETTINGS_TITLE_SIZE * 3.0;
+
+    for (i, entry) in SETTINGS_ENTRIES.iter().enumerate() {
+        let selected = i == menu.selected;
+        let color = if selected { Color::YELLOW } else { Color::WHITE };
+        let label = if selected { format!("> {}", entry.name()) } else { entry.name().to_string() };
+        let value = if selected && menu.waiting_for_key { String::from("PRESS A KEY") } else { setting_text(settings, *entry) };
+        let value_dimensions = BitmapFont::text_dimensions(&value, SETTINGS_TEXT_SIZE);
+
+        assets.font.draw(canvas, &label, Vec2::new(left, y), SETTINGS_TEXT_SIZE, color);
+        assets.font.draw(canvas, &value, Vec2::new(right - value_dimensions.x, y), SETTINGS_TEXT_SIZE, color);
+
+        y += SETTINGS_TEXT_SIZE * 2.0;
+    }
+
+    let help = "LEFT/RIGHT CHANGE  ESC BACK\n* APPLIED AFTER A RESTART";
+    let help_dimensions = BitmapFont::text_dimensions(help, SETTINGS_TEXT_SIZE * 0.75);
+    assets.font.draw(canvas, help, Vec2::new((window_size.x - help_dimensions.x) / 2.0, y + SETTINGS_TEXT_SIZE), SETTINGS_TEXT_SIZE * 0.75, Color::WHITE);
+}
+
+//Multiplies the whole playfield by the filter colour
+pub fn draw_color_filter(canvas: &mut graphics::Canvas, filter: ColorFilter, window_size: Vec2) {
+    if let Some(color) = filter.color() {
+        canvas.set_blend_mode(graphics::BlendMode::MULTIPLY);
+        canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(graphics::Rect::new(0.0, 0.0, window_size.x, window_size.y)).color(color));
+        canvas.set_blend_mode(graphics::BlendMode::ALPHA);
+    }
+}
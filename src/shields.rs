@@ -0,0 +1,96 @@
+use std::mem;
+use half::f16;
+
+use ggez::glam::*;
+
+use crate::actor::{Actor, ActorDeath, ActorType};
+use crate::assets::Assets;
+use crate::game::Window;
+use crate::events::{EventQueue, GameEvent};
+
+pub const SHIELD_LIFE : f32 = 5.0;
+//Time between two bites of the invaders marching over a shield
+pub const SHIELD_EROSION_TIME : f32 = 0.3;
+
+pub fn create_shield() -> Actor {
+    Actor {
+        tag: ActorType::Shield,
+        position: Vec2::ZERO,
+        direction: Vec2::ZERO,
+        size: Vec2::ZERO,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: SHIELD_LIFE,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+    }
+}
+
+pub fn create_shileds(assets: &Assets, window: &Window) -> Vec<Actor> {
+    let mut shields : Vec<Actor> = Vec::new();
+    let x_offset = window.size.x / 7.0;
+    let x_start = (window.size.x - assets.shield_image.height() as f32) / 2.2; //2.2 is a magic number
+
+    for i in 0..3 {
+        let mut shield = create_shield();
+        shield.position = Vec2{ x: -x_start + (x_offset * (1.0 + (i * 2) as f32)), y: window.size.y / 3.5};
+        shield.size = Vec2{ x: assets.shield_image.width() as f32, y: assets.shield_image.height() as f32 };
+        shields.push(shield);
+    }
+
+    shields
+}
+
+//The bullet is absorbed, returns the death event when the shield is destroyed
+pub fn damage_shield(shied_index: usize, shield: &mut Actor, bullet: &mut Actor, damage: f32, events: &mut EventQueue) -> Option<ActorDeath> {
+    bullet.kill();
+    hit_shield(shied_index, shield, damage, events)
+}
+
+//Every shield under the formation loses one hp for each invader overlapping it
+pub fn erode_shields(shields: &mut [Actor], enemies: &[Actor], events: &mut EventQueue) -> Vec<ActorDeath> {
+    let mut deaths = Vec::new();
+
+    for (i, shield) in shields.iter_mut().enumerate() {
+        let shield_rect = shield.get_rect();
+
+        for enemie in enemies {
+            if enemie.is_alive() && shield.is_alive() && enemie.get_rect().overlaps(&shield_rect) {
+                deaths.extend(hit_shield(i, shield, 1.0, events));
+            }
+        }
+    }
+
+    deaths
+}
+
+fn hit_shield(shied_index: usize, shield: &mut Actor, damage: f32, events: &mut EventQueue) -> Option<ActorDeath> {
+    let death = shield.damage(damage);
+    events.push(GameEvent::ShieldDamaged{ index: shied_index, hp: shield.hp.max(0.0) });
+    death
+}
+
+//Restores one hp of a standing shield, destroyed shields stay destroyed
+pub fn repair_shield(shied_index: usize, shield: &mut Actor, events: &mut EventQueue) {
+    if !shield.is_alive() || shield.hp >= SHIELD_LIFE {
+        return;
+    }
+
+    shield.hp += 1.0;
+    events.push(GameEvent::ShieldRepaired{ index: shied_index, hp: shield.hp });
+}
+
+pub fn get_shield_damage_texture(damage_data: &[bool]) -> Vec<u8> {
+    let mut clear_damage_f = vec![f16::from_f32(0.0_f32); 56 * 41 * 4];
+
+    for (i, damage) in damage_data.iter().enumerate() {
+        if !*damage {
+            clear_damage_f[i*4 + 3] = f16::from_f32(1.0_f32);
+        }
+    }
+
+    let len = clear_damage_f.len() * mem::size_of::<f16>();
+    let ptr = clear_damage_f.as_ptr() as *const u8;
+
+    unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec()
+}
@@ -0,0 +1,170 @@
+use std::io::{Read, Write};
+
+use ggez::glam::*;
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::actor::Actor;
+use crate::boss::Boss;
+use crate::enemies::{get_enemies_rect, EnemiesControler};
+use crate::events::GameEvent;
+use crate::game::GameState;
+use crate::hud::{format_score, hud_handle_event, HudTag};
+use crate::lives::ExtraLifeAwards;
+use crate::powerups::ActivePowerups;
+use crate::rng::{game_rng_state, restore_game_rng, GameRngState};
+use crate::ruleset::{Difficulty, Ruleset};
+use crate::scoring::Scoring;
+
+pub const SNAPSHOT_FILE : &str = "/snapshot.json";
+//Bumped every time the layout of Snapshot changes, older saves are rejected
+pub const SNAPSHOT_VERSION : u32 = 2;
+
+//Everything needed to resume a run, the rest of GameState is rebuilt from the ruleset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub window_size: Vec2,
+    pub ruleset: Ruleset,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub player: Actor,
+    pub player_shot_timeout: f32,
+    pub player_shots_fired: u32,
+    pub player_bullets: Vec<Actor>,
+    pub enemy_bullets: Vec<Actor>,
+    pub enemies: Vec<Actor>,
+    pub divers: Vec<Actor>,
+    pub boss: Option<Boss>,
+    pub ufo: Option<Actor>,
+    pub shields: Vec<Actor>,
+    pub powerups: Vec<Actor>,
+    pub active_powerups: ActivePowerups,
+    pub enemies_controler: EnemiesControler,
+    pub extra_life_awards: ExtraLifeAwards,
+    pub scoring: Scoring,
+    pub score: u32,
+    pub max_score: u32,
+    pub wave: u32,
+    pub rng: GameRngState,
+}
+
+//Keeps the state of the game rng so the resumed run draws the same numbers as this one
+pub fn take_snapshot(game: &GameState) -> Snapshot {
+    snapshot_with_rng(game, game_rng_state())
+}
+
+//Snapshot only meant to be drawn, the game rng is not saved
+pub fn take_view_snapshot(game: &GameState) -> Snapshot {
+    snapshot_with_rng(game, GameRngState::default())
+}
+
+fn snapshot_with_rng(game: &GameState, rng: GameRngState) -> Snapshot {
+    Snapshot {
+        version: SNAPSHOT_VERSION,
+        window_size: game.window.size,
+        ruleset: game.ruleset,
+        difficulty: game.difficulty,
+        player: game.player.clone(),
+        player_shot_timeout: game.player_shot_timeout,
+        player_shots_fired: game.player_shots_fired,
+        player_bullets: game.player_bullets.clone(),
+        enemy_bullets: game.enemy_bullets.clone(),
+        enemies: game.enemies.clone(),
+        divers: game.divers.clone(),
+        boss: game.boss.clone(),
+        ufo: game.ufo.clone(),
+        shields: game.shields.clone(),
+        powerups: game.powerups.clone(),
+        active_powerups: game.active_powerups.clone(),
+        enemies_controler: game.enemies_controler.clone(),
+        extra_life_awards: game.extra_life_awards.clone(),
+        scoring: game.scoring.clone(),
+        score: game.score,
+        max_score: game.max_score,
+        wave: game.wave,
+        rng,
+    }
+}
+
+pub fn restore_snapshot(game: &mut GameState, snapshot: Snapshot) {
+    game.ruleset = snapshot.ruleset;
+    game.weapon = snapshot.ruleset.weapon();
+    game.enemy_definitions = snapshot.ruleset.enemy_definitions();
+    game.difficulty = snapshot.difficulty;
+
+    game.player = snapshot.player;
+    game.player_shot_timeout = snapshot.player_shot_timeout;
+    game.player_shots_fired = snapshot.player_shots_fired;
+    game.player_bullets = snapshot.player_bullets;
+    game.enemy_bullets = snapshot.enemy_bullets;
+    game.enemies = snapshot.enemies;
+    game.divers = snapshot.divers;
+    game.boss = snapshot.boss;
+    game.ufo = snapshot.ufo;
+    game.shields = snapshot.shields;
+    game.powerups = snapshot.powerups;
+    game.active_powerups = snapshot.active_powerups;
+    game.enemies_controler = snapshot.enemies_controler;
+    game.enemies_controler.enemies_rect = get_enemies_rect(&game.enemies);
+    game.extra_life_awards = snapshot.extra_life_awards;
+    game.scoring = snapshot.scoring;
+    game.score = snapshot.score;
+    game.max_score = game.max_score.max(snapshot.max_score);
+    game.wave = snapshot.wave;
+    game.deaths.clear();
+    game.events.events.clear();
+    //The run no longer matches its recorded inputs
+    game.replay.tainted = true;
+
+    restore_game_rng(snapshot.rng);
+
+    //The hud only follows events, so it is brought up to date by hand
+    let hud = &mut game.hud;
+    hud.reset_shield_elements();
+    hud_handle_event(hud, &GameEvent::ScoreChanged{ score: game.score });
+    hud.set_text(HudTag::MaxScore, format_score(game.max_score));
+    hud.set_text(HudTag::Powerups, game.active_powerups.timers_text());
+    hud.set_text(HudTag::Combo, game.scoring.combo_text());
+
+    for (i, shield) in game.shields.iter().enumerate() {
+        hud_handle_event(hud, &GameEvent::ShieldDamaged{ index: i, hp: shield.hp.max(0.0) });
+    }
+}
+
+pub fn snapshot_exists(ctx: &Context) -> bool {
+    ctx.fs.is_file(SNAPSHOT_FILE)
+}
+
+pub fn save_snapshot(ctx: &Context, snapshot: &Snapshot) -> GameResult {
+    let text = serde_json::to_string(snapshot).map_err(|error| ggez::GameError::CustomError(error.to_string()))?;
+    let mut file = ctx.fs.create(SNAPSHOT_FILE)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+//The error is a message for the player, the save is left on disk
+pub fn load_snapshot(ctx: &Context, window_size: Vec2) -> Result<Snapshot, String> {
+    let mut text = String::new();
+    ctx.fs.open(SNAPSHOT_FILE)
+        .map_err(|error| error.to_string())?
+        .read_to_string(&mut text)
+        .map_err(|error| error.to_string())?;
+
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(|_| String::from("save file is corrupted"))?;
+
+    //The version is checked first so an old layout is reported as such and not as a corrupted file
+    match value.get("version").and_then(|version| version.as_u64()) {
+        Some(version) if version == SNAPSHOT_VERSION as u64 => (),
+        Some(version) => return Err(format!("save version {} is not supported", version)),
+        None => return Err(String::from("save file has no version"))
+    }
+
+    let snapshot: Snapshot = serde_json::from_value(value).map_err(|_| String::from("save file is corrupted"))?;
+
+    if snapshot.window_size != window_size {
+        return Err(format!("save was made for a {}x{} window", snapshot.window_size.x, snapshot.window_size.y));
+    }
+
+    Ok(snapshot)
+}
@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use ggez::event;
+use ggez::glam::*;
+use ggez::graphics::Color;
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::snapshot::{restore_snapshot, Snapshot};
+use crate::timestep::*;
+
+//Seconds the spectators stay behind the game, it smooths out the network hiccups
+pub const SPECTATOR_DELAY : f32 = 1.0;
+pub const SPECTATOR_TEXT_SIZE : f32 = 14.0;
+//Frames waiting for a spectator's writer thread, a spectator that falls further behind is dropped
+pub const SPECTATOR_QUEUE_SIZE : usize = 120;
+
+//One line of json per tick on the stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectatorFrame {
+    pub tick: u64,
+    pub simulation_rate: u32,
+    pub snapshot: Snapshot,
+}
+
+//Sends the state of every tick to the spectators connected on a local TCP socket
+#[derive(Debug)]
+pub struct SpectatorServer {
+    pub listener: TcpListener,
+    //One writer thread per spectator, a closed or full channel means the spectator left or can't keep up
+    pub clients: Vec<SyncSender<Arc<String>>>,
+    pub tick: u64,
+}
+
+pub fn create_spectator_server(address: &str) -> std::io::Result<SpectatorServer> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+
+    Ok(SpectatorServer {
+        listener,
+        clients: Vec::new(),
+        tick: 0,
+    })
+}
+
+impl SpectatorServer {
+    fn accept_clients(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(false).is_err() {
+                continue;
+            }
+
+            let (sender, receiver) = sync_channel::<Arc<String>>(SPECTATOR_QUEUE_SIZE);
+
+            //Writes block, so a slow spectator never holds the game back
+            thread::spawn(move || {
+                let mut stream = stream;
+
+                for line in receiver {
+                    if stream.write_all(line.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            self.clients.push(sender);
+        }
+    }
+
+    pub fn has_clients(&mut self) -> bool {
+        self.accept_clients();
+        !self.clients.is_empty()
+    }
+
+    pub fn publish(&mut self, snapshot: Snapshot, simulation_rate: u32) {
+        let frame = SpectatorFrame {
+            tick: self.tick,
+            simulation_rate,
+            snapshot,
+        };
+
+        self.tick += 1;
+
+        let line = match serde_json::to_string(&frame) {
+            Ok(text) => Arc::new(text + "\n"),
+            Err(_) => return
+        };
+
+        self.clients.retain(|client| client.try_send(line.clone()).is_ok());
+    }
+}
+
+//Draws a game published by a SpectatorServer, SPECTATOR_DELAY seconds late
+pub struct SpectatorState {
+    //Only used to draw the received snapshots
+    pub game: GameState,
+    pub frames: VecDeque<SpectatorFrame>,
+    pub receiver: Receiver<SpectatorFrame>,
+    pub timestep: FixedTimestep,
+    //Waiting for the delay buffer to fill up
+    pub buffering: bool,
+    pub connected: bool,
+    pub error: Option<String>,
+}
+
+impl SpectatorState {
+    pub fn new(ctx: &mut Context, address: &str) -> GameResult<SpectatorState> {
+        let game = GameState::new_headless(ctx)?;
+
+        let stream = TcpStream::connect(address)?;
+        let (sender, receiver) = channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let frame = match line.ok().and_then(|line| serde_json::from_str(&line).ok()) {
+                    Some(frame) => frame,
+                    None => break
+                };
+
+                if sender.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(SpectatorState {
+            game,
+            frames: VecDeque::new(),
+            receiver,
+            timestep: create_fixed_timestep(DEFAULT_SIMULATION_RATE),
+            buffering: true,
+            connected: true,
+            error: None,
+        })
+    }
+
+    fn delay_frames(&self) -> usize {
+        (SPECTATOR_DELAY * self.timestep.simulation_rate() as f32) as usize
+    }
+
+    fn show_frame(&mut self, frame: SpectatorFrame) {
+        if frame.snapshot.window_size != self.game.window.size {
+            self.error = Some(format!("game window is {}x{}", frame.snapshot.window_size.x, frame.snapshot.window_size.y));
+            return;
+        }
+
+        if frame.simulation_rate != self.timestep.simulation_rate() {
+            self.timestep.set_rate(frame.simulation_rate);
+            self.game.timestep.set_rate(frame.simulation_rate);
+        }
+
+        restore_snapshot(&mut self.game, frame.snapshot);
+    }
+
+    fn status_text(&self) -> String {
+        if let Some(error) = &self.error {
+            return error.to_uppercase();
+        }
+
+        if !self.connected && self.frames.is_empty() {
+            return String::from("GAME ENDED");
+        }
+
+        if self.buffering {
+            return String::from("BUFFERING");
+        }
+
+        String::from("SPECTATING")
+    }
+}
+
+impl event::EventHandler for SpectatorState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(frame) => self.frames.push_back(frame),
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+
+        //The end of the stream is played without waiting
+        if self.buffering && (self.frames.len() >= self.delay_frames() || !self.connected) {
+            self.buffering = false;
+        }
+
+        if self.buffering {
+            return Ok(());
+        }
+
+        self.timestep.advance(ctx.time.delta().as_secs_f32());
+
+        while self.timestep.next_step() {
+            match self.frames.pop_front() {
+                Some(frame) => self.show_frame(frame),
+                None => {
+                    self.buffering = self.connected;
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        //The game draws its own frame, the spectator text goes over it
+        self.game.timestep.accumulator = self.timestep.accumulator;
+        event::EventHandler::draw(&mut self.game, ctx)?;
+
+        let mut canvas = self.game.window.canvas(ctx, None);
+        let text = self.status_text();
+        let text_size = BitmapFont::text_dimensions(&text, SPECTATOR_TEXT_SIZE);
+        let position = Vec2::new(self.game.window.size.x - text_size.x - 8.0, self.game.window.size.y / 2.0);
+        self.game.assets.font.draw(&mut canvas, &text, position, SPECTATOR_TEXT_SIZE, Color::WHITE);
+
+        canvas.finish(ctx)
+    }
+}
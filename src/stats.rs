@@ -0,0 +1,328 @@
+use std::io::{Read, Write};
+
+use ggez::glam::*;
+use ggez::graphics::{self, Color};
+use ggez::{Context, GameResult};
+
+use crate::actor::ActorType;
+use crate::assets::Assets;
+use crate::events::GameEvent;
+use crate::font::BitmapFont;
+use crate::leaderboard::{leaderboard_text, LeaderboardClient};
+
+pub const STATS_FILE : &str = "/stats.txt";
+pub const HIGH_SCORE_FILE : &str = "/highscore.txt";
+pub const SURVIVOR_WAVES : u32 = 10;
+pub const STATS_TEXT_SIZE : f32 = 14.0;
+pub const STATS_TITLE_SIZE : f32 = 28.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Achievement {
+    //Clear a wave without losing a shield
+    Untouchable,
+    //Clear a wave hitting something with every shot
+    Sharpshooter,
+    //Clear SURVIVOR_WAVES waves in a single game
+    Survivor,
+}
+
+pub const ACHIEVEMENTS : [Achievement; 3] = [
+    Achievement::Untouchable,
+    Achievement::Sharpshooter,
+    Achievement::Survivor,
+];
+
+impl Achievement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::Untouchable => "Untouchable",
+            Achievement::Sharpshooter => "Sharpshooter",
+            Achievement::Survivor => "Survivor",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::Untouchable => "Clear a wave without losing a shield",
+            Achievement::Sharpshooter => "Clear a wave with 100% accuracy",
+            Achievement::Survivor => "Survive 10 waves",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Achievement> {
+        ACHIEVEMENTS.iter().find(|achievement| achievement.name() == name).copied()
+    }
+}
+
+//Lifetime statistics, saved in STATS_FILE
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub enemy_a_kills: u32,
+    pub enemy_b_kills: u32,
+    pub enemy_c_kills: u32,
+    pub boss_hits: u32,
+    pub bosses_killed: u32,
+    pub ufos_hit: u32,
+    pub waves_cleared: u32,
+    pub deaths: u32,
+    //Seconds
+    pub play_time: f32,
+    pub achievements: Vec<Achievement>,
+}
+
+//Counters of the wave being played, checked by the achievements when it is cleared
+#[derive(Debug, Default)]
+pub struct WaveStats {
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub shields_lost: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct StatsTracker {
+    pub stats: Stats,
+    pub wave: WaveStats,
+}
+
+impl Stats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+
+        (self.shots_hit as f32 / self.shots_fired as f32).min(1.0)
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.achievements.contains(&achievement)
+    }
+
+    //Returns false when the achievement was already unlocked
+    pub fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.is_unlocked(achievement) {
+            return false;
+        }
+
+        self.achievements.push(achievement);
+        true
+    }
+
+    //One key=value pair per line
+    pub fn to_text(&self) -> String {
+        let achievements: Vec<&str> = self.achievements.iter().map(|achievement| achievement.name()).collect();
+
+        format!("shots_fired={}\nshots_hit={}\nenemy_a_kills={}\nenemy_b_kills={}\nenemy_c_kills={}\nboss_hits={}\nbosses_killed={}\nufos_hit={}\nwaves_cleared={}\ndeaths={}\nplay_time={}\nachievements={}\n",
+            self.shots_fired, self.shots_hit, self.enemy_a_kills, self.enemy_b_kills, self.enemy_c_kills,
+            self.boss_hits, self.bosses_killed, self.ufos_hit, self.waves_cleared, self.deaths, self.play_time, achievements.join(","))
+    }
+
+    //Unknown keys and malformed values are skipped
+    pub fn from_text(text: &str) -> Stats {
+        let mut stats = Stats::default();
+
+        for line in text.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => continue
+            };
+
+            let counter = value.trim().parse::<u32>().ok();
+
+            match key.trim() {
+                "shots_fired" => stats.shots_fired = counter.unwrap_or(0),
+                "shots_hit" => stats.shots_hit = counter.unwrap_or(0),
+                "enemy_a_kills" => stats.enemy_a_kills = counter.unwrap_or(0),
+                "enemy_b_kills" => stats.enemy_b_kills = counter.unwrap_or(0),
+                "enemy_c_kills" => stats.enemy_c_kills = counter.unwrap_or(0),
+                "boss_hits" => stats.boss_hits = counter.unwrap_or(0),
+                "bosses_killed" => stats.bosses_killed = counter.unwrap_or(0),
+                "ufos_hit" => stats.ufos_hit = counter.unwrap_or(0),
+                "waves_cleared" => stats.waves_cleared = counter.unwrap_or(0),
+                "deaths" => stats.deaths = counter.unwrap_or(0),
+                "play_time" => stats.play_time = value.trim().parse().unwrap_or(0.0),
+                "achievements" => stats.achievements = value.split(',').filter_map(|name| Achievement::from_name(name.trim())).collect(),
+                _ => ()
+            }
+        }
+
+        stats
+    }
+}
+
+impl StatsTracker {
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        let stats = &mut self.stats;
+
+        match *event {
+            //The accuracy counts every projectile
+            GameEvent::PlayerShot{ projectiles } => {
+                stats.shots_fired += projectiles;
+                self.wave.shots_fired += projectiles;
+            },
+            GameEvent::ShotHit => {
+                stats.shots_hit += 1;
+                self.wave.shots_hit += 1;
+            },
+            GameEvent::EnemyKilled{ kind, .. } => {
+                match kind {
+                    ActorType::EnemyA => stats.enemy_a_kills += 1,
+                    ActorType::EnemyB => stats.enemy_b_kills += 1,
+                    ActorType::EnemyC => stats.enemy_c_kills += 1,
+                    ActorType::Ufo => stats.ufos_hit += 1,
+                    _ => ()
+                }
+            },
+            GameEvent::BossDamaged{ .. } => stats.boss_hits += 1,
+            GameEvent::BossKilled{ .. } => stats.bosses_killed += 1,
+            GameEvent::PlayerHit{ .. } => stats.deaths += 1,
+            GameEvent::ShieldDamaged{ hp, .. } if hp <= 0.0 => self.wave.shields_lost += 1,
+            GameEvent::WaveCleared{ wave, shields_standing } => {
+                stats.waves_cleared += 1;
+
+                //Nothing to lose once every shield is gone, so those waves don't count
+                if self.wave.shields_lost == 0 && shields_standing > 0 {
+                    stats.unlock(Achievement::Untouchable);
+                }
+
+                if self.wave.shots_fired > 0 && self.wave.shots_hit >= self.wave.shots_fired {
+                    stats.unlock(Achievement::Sharpshooter);
+                }
+
+                if wave >= SURVIVOR_WAVES {
+                    stats.unlock(Achievement::Survivor);
+                }
+
+                self.wave = WaveStats::default();
+            },
+            GameEvent::GameOver{ .. } => self.wave = WaveStats::default(),
+            _ => ()
+        }
+    }
+}
+
+//A missing or unreadable file gives empty stats
+pub fn load_stats(ctx: &Context) -> Stats {
+    match read_user_file(ctx, STATS_FILE) {
+        Some(text) => Stats::from_text(&text),
+        None => Stats::default()
+    }
+}
+
+pub fn save_stats(ctx: &Context, stats: &Stats) -> GameResult {
+    write_user_file(ctx, STATS_FILE, &stats.to_text())
+}
+
+pub fn load_high_score(ctx: &Context) -> u32 {
+    read_user_file(ctx, HIGH_SCORE_FILE).and_then(|text| text.trim().parse().ok()).unwrap_or(0)
+}
+
+pub fn save_high_score(ctx: &Context, score: u32) -> GameResult {
+    write_user_file(ctx, HIGH_SCORE_FILE, &format!("{}\n", score))
+}
+
+pub fn read_user_file(ctx: &Context, path: &str) -> Option<String> {
+    if !ctx.fs.is_file(path) {
+        return None;
+    }
+
+    let mut text = String::new();
+    ctx.fs.open(path).ok()?.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+pub fn write_user_file(ctx: &Context, path: &str, text: &str) -> GameResult {
+    let mut file = ctx.fs.create(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn stats_text(stats: &Stats) -> String {
+    let minutes = (stats.play_time / 60.0) as u32;
+
+    let mut lines = vec![
+        format!("SHOTS FIRED    {}", stats.shots_fired),
+        format!("ACCURACY       {:.0}%", stats.accuracy() * 100.0),
+        format!("KILLS A/B/C    {}/{}/{}", stats.enemy_a_kills, stats.enemy_b_kills, stats.enemy_c_kills),
+        format!("BOSS HITS      {}", stats.boss_hits),
+        format!("BOSSES         {}", stats.bosses_killed),
+        format!("UFOS HIT       {}", stats.ufos_hit),
+        format!("WAVES CLEARED  {}", stats.waves_cleared),
+        format!("DEATHS         {}", stats.deaths),
+        format!("PLAY TIME      {}:{:02}", minutes / 60, minutes % 60),
+        String::new(),
+        String::from("ACHIEVEMENTS"),
+    ];
+
+    for achievement in ACHIEVEMENTS {
+        let mark = if stats.is_unlocked(achievement) { "*" } else { "-" };
+        lines.push(format!("{} {}: {}", mark, achievement.name(), achievement.description()));
+    }
+
+    lines.join("\n")
+}
+
+pub fn draw_stats_screen(canvas: &mut graphics::Canvas, assets: &Assets, stats: &Stats, leaderboard: &LeaderboardClient, window_size: Vec2) {
+    canvas.set_sampler(graphics::Sampler::nearest_clamp());
+
+    let title = "STATISTICS";
+    let title_dimensions = BitmapFont::text_dimensions(title, STATS_TITLE_SIZE);
+    let title_position = Vec2::new((window_size.x - title_dimensions.x) / 2.0, window_size.y / 8.0);
+    assets.font.draw(canvas, title, title_position, STATS_TITLE_SIZE, Color::WHITE);
+
+    let text = format!("{}\n\n{}", stats_text(stats), leaderboard_text(leaderboard));
+    let text_dimensions = BitmapFont::text_dimensions(&text, STATS_TEXT_SIZE);
+    let text_position = Vec2::new((window_size.x - text_dimensions.x) / 2.0, title_position.y + title_dimensions.y * 2.0);
+    assets.font.draw(canvas, &text, text_position, STATS_TEXT_SIZE, Color::WHITE);
+
+    let hint = "PRESS TAB TO RETURN";
+    let hint_dimensions = BitmapFont::text_dimensions(hint, STATS_TEXT_SIZE);
+    assets.font.draw(canvas, hint, Vec2::new((window_size.x - hint_dimensions.x) / 2.0, window_size.y - window_size.y / 8.0), STATS_TEXT_SIZE, Color::WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear_wave(tracker: &mut StatsTracker, shields_standing: usize) {
+        tracker.handle_event(&GameEvent::WaveCleared{ wave: 1, shields_standing });
+    }
+
+    #[test]
+    fn untouchable_needs_a_wave_without_shield_losses() {
+        let mut tracker = StatsTracker::default();
+        tracker.handle_event(&GameEvent::ShieldDamaged{ index: 0, hp: 0.0 });
+        clear_wave(&mut tracker, 2);
+        assert!(!tracker.stats.is_unlocked(Achievement::Untouchable));
+
+        tracker.handle_event(&GameEvent::ShieldDamaged{ index: 1, hp: 3.0 });
+        clear_wave(&mut tracker, 2);
+        assert!(tracker.stats.is_unlocked(Achievement::Untouchable));
+    }
+
+    #[test]
+    fn every_projectile_of_a_shot_counts_for_the_accuracy() {
+        let mut tracker = StatsTracker::default();
+        tracker.handle_event(&GameEvent::PlayerShot{ projectiles: 3 });
+        tracker.handle_event(&GameEvent::ShotHit);
+        tracker.handle_event(&GameEvent::ShotHit);
+        clear_wave(&mut tracker, 3);
+        assert_eq!(tracker.stats.shots_fired, 3);
+        assert!(!tracker.stats.is_unlocked(Achievement::Sharpshooter));
+
+        tracker.handle_event(&GameEvent::PlayerShot{ projectiles: 3 });
+        for _ in 0..3 {
+            tracker.handle_event(&GameEvent::ShotHit);
+        }
+        clear_wave(&mut tracker, 3);
+        assert!(tracker.stats.is_unlocked(Achievement::Sharpshooter));
+    }
+
+    #[test]
+    fn untouchable_is_not_free_once_every_shield_is_gone() {
+        let mut tracker = StatsTracker::default();
+        clear_wave(&mut tracker, 0);
+        assert!(!tracker.stats.is_unlocked(Achievement::Untouchable));
+    }
+}
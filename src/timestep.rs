@@ -0,0 +1,50 @@
+pub const DEFAULT_SIMULATION_RATE : u32 = 60;
+//Steps run in a single frame at most, after a long stall the remaining time is dropped
+pub const MAX_STEPS_PER_FRAME : u32 = 5;
+
+//Runs the simulation in constant steps whatever the frame rate is
+#[derive(Debug)]
+pub struct FixedTimestep {
+    //Seconds simulated by every step
+    pub step_time: f32,
+    //Real time not simulated yet
+    pub accumulator: f32,
+}
+
+pub fn create_fixed_timestep(simulation_rate: u32) -> FixedTimestep {
+    FixedTimestep {
+        step_time: 1.0 / simulation_rate.max(1) as f32,
+        accumulator: 0.0,
+    }
+}
+
+impl FixedTimestep {
+    pub fn set_rate(&mut self, simulation_rate: u32) {
+        self.step_time = 1.0 / simulation_rate.max(1) as f32;
+        self.accumulator = 0.0;
+    }
+
+    pub fn simulation_rate(&self) -> u32 {
+        (1.0 / self.step_time).round() as u32
+    }
+
+    //Adds the real time of the last frame
+    pub fn advance(&mut self, frame_time: f32) {
+        self.accumulator = (self.accumulator + frame_time).min(self.step_time * MAX_STEPS_PER_FRAME as f32);
+    }
+
+    //Returns true while a whole step is waiting to be simulated
+    pub fn next_step(&mut self) -> bool {
+        if self.accumulator < self.step_time {
+            return false;
+        }
+
+        self.accumulator -= self.step_time;
+        true
+    }
+
+    //Fraction of the next step already elapsed, used to interpolate the rendering
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step_time).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,66 @@
+use ggez::glam::*;
+
+use crate::actor::{Actor, ActorType};
+use crate::assets::Assets;
+
+pub const UFO_LIFE : f32 = 1.0;
+pub const UFO_SPEED : f32 = 100.0;
+pub const UFO_SPAWN_TIME : f32 = 25.0;
+pub const UFO_MIN_ENEMIES : usize = 8;
+pub const UFO_TOP_OFFSET : f32 = 80.0;
+
+//Arcade mystery score table, the pointer moves on every player shot including the one that hits the ufo,
+//so the 8th shot and every 15th after it (23rd, 38th, ...) scores 300
+pub const UFO_SCORE_TABLE : [u32; 15] = [100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100];
+
+pub fn ufo_score(shots_fired: u32) -> u32 {
+    UFO_SCORE_TABLE[shots_fired as usize % UFO_SCORE_TABLE.len()]
+}
+
+//The ufo enters from the left on an even shot count and from the right on an odd one
+pub fn create_ufo(assets: &Assets, window_size: Vec2, shots_fired: u32) -> Actor {
+    let size = Vec2{ x: assets.ufo_image.width() as f32, y: assets.ufo_image.height() as f32 };
+    let from_left = shots_fired.is_multiple_of(2);
+    let start_x = window_size.x / 2.0 - size.x / 2.0;
+
+    Actor {
+        tag: ActorType::Ufo,
+        position: Vec2{ x: if from_left { -start_x } else { start_x }, y: -window_size.y / 2.0 + UFO_TOP_OFFSET },
+        direction: Vec2{ x: if from_left { 1.0 } else { -1.0 }, y: 0.0 },
+        size,
+        scale: Vec2{ x: 1.0, y: 1.0 },
+        hp: UFO_LIFE,
+        alive: true,
+        previous_position: None,
+        has_hit: false,
+    }
+}
+
+pub fn update_ufo_position(actor: &mut Actor, dt: f32) {
+    actor.position += actor.direction * (UFO_SPEED * dt);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_fifteenth_shot_from_the_eighth_scores_300() {
+        let cases = [
+            (1, 50),
+            (7, 50),
+            (8, 300),
+            (9, 100),
+            (15, 100),
+            (22, 50),
+            (23, 300),
+            (24, 100),
+            (38, 300),
+            (53, 300),
+        ];
+
+        for (shots_fired, score) in cases {
+            assert_eq!(ufo_score(shots_fired), score, "shot {}", shots_fired);
+        }
+    }
+}
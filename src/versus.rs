@@ -0,0 +1,389 @@
+use ggez::event;
+use ggez::glam::*;
+use ggez::graphics::Color;
+use ggez::{Context, GameResult};
+
+use crate::actor::{create_bullet, ActorType};
+use crate::audio::play_event_sound;
+use crate::enemies::{create_enemy, ENEMY_SCALE};
+use crate::events::GameEvent;
+use crate::font::BitmapFont;
+use crate::game::GameState;
+use crate::input::{InputState, KeyBindings};
+use crate::netplay::{create_net_session, create_rollback, NetSession, Rollback};
+use crate::rng::seed_game_rng;
+use crate::ruleset::{Difficulty, Ruleset};
+use crate::scoring::ScoringMode;
+use crate::settings::load_settings;
+use crate::snapshot::{restore_snapshot, take_snapshot, Snapshot};
+use crate::timestep::*;
+
+//Kills needed to send one attack to the opponent
+pub const VERSUS_KILLS_PER_ATTACK : u32 = 3;
+//Distance from the top of the screen the attacks come from
+pub const ATTACK_SPAWN_MARGIN : f32 = 40.0;
+pub const VERSUS_TEXT_SIZE : f32 = 14.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Attack {
+    //An invader diving at the player
+    Invader,
+    FastBullet,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatchResult {
+    Winner(usize),
+    Draw,
+}
+
+//Part of the versus simulation outside the two games
+#[derive(Debug, Clone, Default)]
+pub struct VersusMatch {
+    pub kills: [u32; 2],
+    pub attacks_sent: [u32; 2],
+    pub result: Option<MatchResult>,
+}
+
+impl VersusMatch {
+    //Returns the attacks the player sends to the opponent with the events of the tick
+    pub fn handle_events(&mut self, player: usize, events: &[GameEvent]) -> Vec<Attack> {
+        let mut attacks = Vec::new();
+
+        for event in events {
+            if let GameEvent::EnemyKilled{ kind: ActorType::EnemyA | ActorType::EnemyB | ActorType::EnemyC, .. } = event {
+                self.kills[player] += 1;
+
+                if self.kills[player].is_multiple_of(VERSUS_KILLS_PER_ATTACK) {
+                    let attack = if self.attacks_sent[player].is_multiple_of(2) { Attack::Invader } else { Attack::FastBullet };
+                    self.attacks_sent[player] += 1;
+                    attacks.push(attack);
+                }
+            }
+        }
+
+        attacks
+    }
+
+    //The first player to lose a game loses the match
+    pub fn check_result(&mut self, game_over: [bool; 2]) {
+        self.result = match game_over {
+            [true, true] => Some(MatchResult::Draw),
+            [true, false] => Some(MatchResult::Winner(1)),
+            [false, true] => Some(MatchResult::Winner(0)),
+            [false, false] => None
+        };
+    }
+}
+
+pub fn send_attack(game: &mut GameState, attack: Attack) {
+    let spawn_position = Vec2::new(game.player.position.x, -game.window.size.y / 2.0 + ATTACK_SPAWN_MARGIN);
+
+    match attack {
+        Attack::Invader => {
+            let image = &game.assets.enemie_images[0];
+            let mut invader = create_enemy();
+
+            invader.size = Vec2::new(image.width() as f32, image.height() as f32) * ENEMY_SCALE;
+            invader.scale = Vec2::splat(ENEMY_SCALE);
+            invader.position = spawn_position;
+            invader.direction = Vec2::new(0.0, 1.0);
+
+            if let Some(definition) = game.enemy_definitions.get(invader.tag) {
+                invader.hp = definition.hp;
+            }
+
+            game.divers.push(invader);
+        },
+        Attack::FastBullet => {
+            let mut bullet = create_bullet(ActorType::EnemyBulletFast);
+            bullet.position = spawn_position;
+            bullet.direction = Vec2::new(0.0, 1.0);
+            game.enemy_bullets.push(bullet);
+        }
+    }
+}
+
+//Both games and the match, saved at the start of every tick
+#[derive(Debug, Clone)]
+pub struct VersusSnapshot {
+    pub snapshots: Vec<Snapshot>,
+    pub versus: VersusMatch,
+}
+
+//Two players on different machines, both sides simulate both games from the inputs of every tick
+pub struct VersusState {
+    //Indexed by player, the local one is session.player_index
+    pub games: Vec<GameState>,
+    pub versus: VersusMatch,
+    pub session: NetSession,
+    pub input: InputState,
+    pub key_bindings: KeyBindings,
+    pub timestep: FixedTimestep,
+    pub rollback: Rollback<VersusSnapshot>,
+    pub started: bool,
+}
+
+impl VersusState {
+    pub fn new(ctx: &mut Context, local_address: &str, remote_address: &str) -> GameResult<VersusState> {
+        let mut games = vec![GameState::new_headless(ctx)?, GameState::new_headless(ctx)?];
+        let settings = load_settings(&ctx.fs);
+
+        for game in &mut games {
+            game.assets.set_sound_volume(settings.sound_volume());
+        }
+
+        let session = create_net_session(local_address, remote_address, games[0].window.size)?;
+
+        Ok(VersusState {
+            games,
+            versus: VersusMatch::default(),
+            session,
+            input: InputState::default(),
+            key_bindings: settings.key_bindings,
+            timestep: create_fixed_timestep(DEFAULT_SIMULATION_RATE),
+            rollback: create_rollback(),
+            started: false,
+        })
+    }
+
+    fn local_game(&self) -> &GameState {
+        &self.games[self.session.player_index]
+    }
+
+    //Both sides reset the games the same way from the same seed
+    fn start_match(&mut self, seed: u64) {
+        seed_game_rng(seed);
+
+        for game in &mut self.games {
+            game.god_mode = false;
+            game.set_ruleset(Ruleset::Modern);
+            game.set_difficulty(Difficulty::Normal);
+            game.set_scoring_mode(ScoringMode::default());
+            game.set_simulation_rate(DEFAULT_SIMULATION_RATE);
+            game.events.events.clear();
+            game.replay.tainted = true;
+        }
+
+        self.started = true;
+    }
+
+    fn simulate_tick(&mut self, ctx: &Context, play_sounds: bool) -> GameResult {
+        let local = self.session.player_index;
+
+        //Taken every tick, both snapshots hold the game rng as it is at the start of the tick
+        let snapshots = self.games.iter().map(take_snapshot).collect();
+        let inputs = self.rollback.begin_tick(&self.session, VersusSnapshot{ snapshots, versus: self.versus.clone() });
+        let mut game_over = [false; 2];
+        let mut attacks = [Vec::new(), Vec::new()];
+
+        for (player, game) in self.games.iter_mut().enumerate() {
+            game.input = InputState::from_bits(inputs[player]);
+            game.event_outbox = Some(Vec::new());
+            game.step(ctx, self.timestep.step_time)?;
+
+            let events = game.event_outbox.take().unwrap_or_default();
+            game_over[player] = events.iter().any(|event| matches!(event, GameEvent::GameOver{ .. }));
+            attacks[player] = self.versus.handle_events(player, &events);
+
+            if play_sounds && player == local {
+                for event in &events {
+                    play_event_sound(ctx, &mut game.assets, event)?;
+                }
+            }
+        }
+
+        for (player, player_attacks) in attacks.into_iter().enumerate() {
+            for attack in player_attacks {
+                send_attack(&mut self.games[1 - player], attack);
+            }
+        }
+
+        self.versus.check_result(game_over);
+        self.rollback.end_tick();
+
+        Ok(())
+    }
+
+    //Plays again from the first tick whose predicted remote input turned out wrong
+    fn rollback(&mut self, ctx: &Context) -> GameResult {
+        if let Some(tick_state) = self.rollback.take_mispredicted(&self.session) {
+            let end_tick = self.rollback.tick;
+
+            for (game, snapshot) in self.games.iter_mut().zip(tick_state.state.snapshots) {
+                restore_snapshot(game, snapshot);
+            }
+
+            self.versus = tick_state.state.versus;
+            self.rollback.tick = tick_state.tick;
+
+            while self.rollback.tick < end_tick && self.versus.result.is_none() {
+                self.simulate_tick(ctx, false)?;
+            }
+        }
+
+        self.rollback.forget_confirmed(&self.session);
+
+        Ok(())
+    }
+
+    fn advance_tick(&mut self, ctx: &Context) -> GameResult {
+        self.rollback(ctx)?;
+
+        //Too far ahead of the opponent, waiting for its inputs
+        if self.versus.result.is_some() || self.rollback.is_waiting(&self.session) {
+            return Ok(());
+        }
+
+        self.session.push_local_input(self.rollback.tick, self.input.to_bits());
+        self.simulate_tick(ctx, true)
+    }
+
+    //The result is only final when no rollback can change it anymore
+    fn confirmed_result(&self) -> Option<MatchResult> {
+        if self.rollback.history.is_empty() {
+            self.versus.result
+        }
+        else {
+            None
+        }
+    }
+
+    fn status_text(&self) -> String {
+        let opponent = &self.games[1 - self.session.player_index];
+
+        if let Some(error) = &self.session.error {
+            return error.to_uppercase();
+        }
+
+        if !self.started {
+            return format!("WAITING FOR {}", self.session.remote);
+        }
+
+        let result = match self.confirmed_result() {
+            Some(MatchResult::Winner(player)) if player == self.session.player_index => "YOU WIN",
+            Some(MatchResult::Winner(_)) => "YOU LOSE",
+            Some(MatchResult::Draw) => "DRAW",
+            None if self.rollback.is_waiting(&self.session) => "WAITING FOR OPPONENT",
+            None => ""
+        };
+
+        format!("OPPONENT {} LIVES {}\n{}", opponent.score, opponent.player.hp.max(0.0), result)
+    }
+}
+
+impl event::EventHandler for VersusState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.session.receive();
+
+        if !self.session.remote_started {
+            self.session.send_hello();
+        }
+
+        if !self.started {
+            if let (Some(seed), None) = (self.session.match_seed, &self.session.error) {
+                self.start_match(seed);
+            }
+
+            return Ok(());
+        }
+
+        self.timestep.advance(ctx.time.delta().as_secs_f32());
+
+        while self.timestep.next_step() {
+            self.advance_tick(ctx)?;
+        }
+
+        //Sent every frame, even after the end, so the opponent can confirm the last ticks
+        self.session.send_inputs();
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let local = self.session.player_index;
+        self.games[local].timestep.accumulator = self.timestep.accumulator;
+
+        //The game draws its own frame, the versus text goes over it
+        let mut canvas = if self.started {
+            event::EventHandler::draw(&mut self.games[local], ctx)?;
+            self.local_game().window.canvas(ctx, None)
+        }
+        else {
+            self.local_game().window.canvas(ctx, Some(Color::BLACK))
+        };
+
+        let text = self.status_text();
+        let window_size = self.local_game().window.size;
+        let text_size = BitmapFont::text_dimensions(&text, VERSUS_TEXT_SIZE);
+        let position = Vec2::new(window_size.x - text_size.x - 8.0, window_size.y / 2.0);
+        self.local_game().assets.font.draw(&mut canvas, &text, position, VERSUS_TEXT_SIZE, Color::WHITE);
+
+        canvas.finish(ctx)
+    }
+
+    fn key_down_event(&mut self, _ctx: &mut Context, input: ggez::input::keyboard::KeyInput, _repeated: bool) -> GameResult {
+        if let Some(key) = input.keycode {
+            self.input.set_key(key, true, &self.key_bindings);
+        }
+
+        Ok(())
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, input: ggez::input::keyboard::KeyInput) -> GameResult {
+        if let Some(key) = input.keycode {
+            self.input.set_key(key, false, &self.key_bindings);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(kind: ActorType) -> GameEvent {
+        GameEvent::EnemyKilled{ kind, pos: Vec2::ZERO }
+    }
+
+    #[test]
+    fn every_third_kill_sends_an_attack() {
+        let mut versus = VersusMatch::default();
+
+        assert!(versus.handle_events(0, &[kill(ActorType::EnemyA), kill(ActorType::EnemyB)]).is_empty());
+        assert_eq!(versus.handle_events(0, &[kill(ActorType::EnemyC)]), vec![Attack::Invader]);
+
+        let kills = vec![kill(ActorType::EnemyA); 6];
+        assert_eq!(versus.handle_events(0, &kills), vec![Attack::FastBullet, Attack::Invader]);
+
+        assert_eq!(versus.kills, [9, 0]);
+        assert_eq!(versus.attacks_sent, [3, 0]);
+    }
+
+    #[test]
+    fn ufos_and_other_events_send_nothing() {
+        let mut versus = VersusMatch::default();
+        let events = [kill(ActorType::Ufo), kill(ActorType::Ufo), kill(ActorType::Ufo), GameEvent::PlayerShot{ projectiles: 3 }, GameEvent::ExtraLife];
+
+        assert!(versus.handle_events(1, &events).is_empty());
+        assert_eq!(versus.kills, [0, 0]);
+    }
+
+    #[test]
+    fn the_first_game_over_loses_the_match() {
+        let mut versus = VersusMatch::default();
+
+        versus.check_result([false, false]);
+        assert_eq!(versus.result, None);
+
+        versus.check_result([false, true]);
+        assert_eq!(versus.result, Some(MatchResult::Winner(0)));
+
+        versus.check_result([true, false]);
+        assert_eq!(versus.result, Some(MatchResult::Winner(1)));
+
+        versus.check_result([true, true]);
+        assert_eq!(versus.result, Some(MatchResult::Draw));
+    }
+}
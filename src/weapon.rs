@@ -0,0 +1,94 @@
+use ggez::glam::*;
+
+use crate::actor::{create_bullet, Actor, ActorType, PLAYER_SHOT_TIME};
+
+pub const PLAYER_BULLET_DAMAGE : f32 = 1.0;
+pub const ARCADE_SHOT_TIME : f32 = 0.1;
+pub const SPREAD_GUN_SPREAD : f32 = 0.15;
+//Player bullets are wider than their sprite so enemy bullets can be shot down
+pub const PLAYER_BULLET_HITBOX_SCALE : Vec2 = Vec2{ x: 8.0, y: 1.0 };
+
+#[derive(Debug, Copy, Clone)]
+pub struct Weapon {
+    pub projectile: ActorType,
+    //Time between two shots
+    pub fire_time: f32,
+    //Bullets fired with a single shot, fanned out by spread
+    pub projectiles: u32,
+    pub spread: f32,
+    pub damage: f32,
+    pub piercing: bool,
+    //Player bullets allowed on screen at once, None means no limit
+    pub max_bullets: Option<usize>,
+    pub hitbox_scale: Vec2,
+}
+
+pub fn create_blaster() -> Weapon {
+    Weapon {
+        projectile: ActorType::PlayerBullet,
+        fire_time: PLAYER_SHOT_TIME,
+        projectiles: 1,
+        spread: 0.0,
+        damage: PLAYER_BULLET_DAMAGE,
+        piercing: false,
+        max_bullets: None,
+        hitbox_scale: PLAYER_BULLET_HITBOX_SCALE,
+    }
+}
+
+//The arcade rule, the next shot is available as soon as the previous bullet is gone
+pub fn create_arcade_cannon() -> Weapon {
+    Weapon {
+        fire_time: ARCADE_SHOT_TIME,
+        max_bullets: Some(1),
+        ..create_blaster()
+    }
+}
+
+pub fn create_spread_gun() -> Weapon {
+    Weapon {
+        projectiles: 3,
+        spread: SPREAD_GUN_SPREAD,
+        ..create_blaster()
+    }
+}
+
+pub fn create_piercing_laser() -> Weapon {
+    Weapon {
+        piercing: true,
+        damage: PLAYER_BULLET_DAMAGE * 2.0,
+        ..create_blaster()
+    }
+}
+
+impl Weapon {
+    //Bullets of a single shot, the bullet limit wins over the projectiles of a spread
+    pub fn shot_projectiles(&self) -> u32 {
+        match self.max_bullets {
+            Some(max_bullets) => self.projectiles.clamp(1, max_bullets.max(1) as u32),
+            None => self.projectiles.max(1)
+        }
+    }
+
+    //The whole shot has to fit under the bullet limit
+    pub fn can_fire(&self, bullets_on_screen: usize) -> bool {
+        match self.max_bullets {
+            Some(max_bullets) => bullets_on_screen + self.shot_projectiles() as usize <= max_bullets,
+            None => true
+        }
+    }
+
+    //Creates the bullets of a single shot, bullet_size is the size of the projectile sprite
+    pub fn fire(&self, position: Vec2, bullet_size: Vec2) -> Vec<Actor> {
+        let projectiles = self.shot_projectiles();
+        let middle = (projectiles - 1) as f32 / 2.0;
+
+        (0..projectiles).map(|i| {
+            let mut bullet = create_bullet(self.projectile);
+            bullet.position = position;
+            bullet.size = bullet_size * self.hitbox_scale;
+            bullet.direction = Vec2{ x: (i as f32 - middle) * self.spread, y: -1.0 };
+            bullet
+        }).collect()
+    }
+}